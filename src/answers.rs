@@ -0,0 +1,302 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Display};
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::input::Loader;
+use crate::registry::{input_name, Registry};
+use crate::solution::{self, Part};
+
+/// Name of the manifest inside an input directory.
+pub const ANSWERS_FILE: &str = "answers.txt";
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Key {
+    pub year: u32,
+    pub day: u32,
+    pub part: Part,
+    pub input: String,
+}
+
+/// Expected answers, one per line: `<year> <day> <part> <input name> <answer>`.
+/// The answer is the rest of the line, blank lines and lines starting with `#` are skipped.
+#[derive(Debug, Default)]
+pub struct Answers {
+    expected: BTreeMap<Key, String>,
+}
+
+impl Answers {
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut expected = BTreeMap::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, answer) =
+                parse_line(line).map_err(|err| anyhow!("line {}: {}", i + 1, err))?;
+            if expected.insert(key, answer.to_owned()).is_some() {
+                bail!("line {}: duplicate answer", i + 1);
+            }
+        }
+        Ok(Self { expected })
+    }
+
+    pub fn load(loader: &Loader) -> Result<Self> {
+        let input = loader.load(ANSWERS_FILE)?;
+        Self::parse(input.raw()).map_err(|err| anyhow!("{}: {}", ANSWERS_FILE, err))
+    }
+
+    pub fn get(&self, key: &Key) -> Option<&str> {
+        self.expected.get(key).map(|answer| answer.as_str())
+    }
+
+    fn inputs(&self, year: u32, day: u32) -> BTreeSet<&str> {
+        self.expected
+            .keys()
+            .filter(|key| key.year == year && key.day == day)
+            .map(|key| key.input.as_str())
+            .collect()
+    }
+}
+
+fn parse_line(line: &str) -> Result<(Key, &str)> {
+    let mut tokens = line.splitn(5, char::is_whitespace);
+    let mut next = |expected: &str| {
+        tokens
+            .next()
+            .filter(|token| !token.is_empty())
+            .ok_or_else(|| anyhow!("expected {}", expected))
+    };
+    let year = next("a year")?;
+    let day = next("a day")?;
+    let part = next("a part")?;
+    let input = next("an input name")?;
+    let answer = next("an answer")?.trim();
+    let key = Key {
+        year: year.parse().map_err(|_| anyhow!("invalid year {}", year))?,
+        day: day.parse().map_err(|_| anyhow!("invalid day {}", day))?,
+        part: part.parse().map_err(|err: String| anyhow!(err))?,
+        input: input.to_owned(),
+    };
+    Ok((key, answer))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Fail { expected: String, actual: String },
+    Missing,
+    Error(String),
+}
+
+#[derive(Debug)]
+pub struct Check {
+    pub key: Key,
+    pub status: Status,
+}
+
+/// Runs every registered day on every input it has answers for. Days without answers are
+/// reported as missing and not run.
+pub fn verify(registry: &Registry, loader: &Loader, answers: &Answers) -> Vec<Check> {
+    let mut checks = Vec::new();
+    for solver in registry.iter() {
+        let (year, day) = (solver.year(), solver.day());
+        let mut inputs = answers.inputs(year, day);
+        let default_input = input_name(year, day);
+        if inputs.is_empty() {
+            inputs.insert(&default_input);
+        }
+        for input in inputs {
+            let keys: Vec<Key> = [Part::One, Part::Two]
+                .into_iter()
+                .map(|part| Key {
+                    year,
+                    day,
+                    part,
+                    input: input.to_owned(),
+                })
+                .collect();
+            let parts: Vec<Part> = keys
+                .iter()
+                .filter(|key| answers.get(key).is_some())
+                .map(|key| key.part)
+                .collect();
+            let result = if parts.is_empty() {
+                Ok(Vec::new())
+            } else {
+                loader
+                    .load(input)
+                    .and_then(|input| solution::solve(solver, input.raw(), &parts))
+            };
+            for key in keys {
+                let status = match (answers.get(&key), &result) {
+                    (None, _) => Status::Missing,
+                    (Some(_), Err(err)) => Status::Error(format!("{:#}", err)),
+                    (Some(expected), Ok(actual)) => {
+                        let index = parts.iter().position(|part| *part == key.part).unwrap();
+                        if actual[index] == expected {
+                            Status::Pass
+                        } else {
+                            Status::Fail {
+                                expected: expected.to_owned(),
+                                actual: actual[index].clone(),
+                            }
+                        }
+                    }
+                };
+                checks.push(Check { key, status });
+            }
+        }
+    }
+
+    let registered: BTreeSet<(u32, u32)> = registry
+        .iter()
+        .map(|solver| (solver.year(), solver.day()))
+        .collect();
+    for key in answers.expected.keys() {
+        if !registered.contains(&(key.year, key.day)) {
+            let status = Status::Error("no solution registered".to_owned());
+            checks.push(Check {
+                key: key.clone(),
+                status,
+            });
+        }
+    }
+    checks
+}
+
+pub fn is_ok(checks: &[Check]) -> bool {
+    checks
+        .iter()
+        .all(|check| matches!(check.status, Status::Pass | Status::Missing))
+}
+
+/// Fixed-width table of `checks` with a summary line.
+pub struct Report<'a>(pub &'a [Check]);
+
+impl Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self
+            .0
+            .iter()
+            .map(|check| check.key.input.len())
+            .max()
+            .unwrap_or(0)
+            .max("input".len());
+        writeln!(f, "year day part {:width$} status", "input", width = width)?;
+        let mut counts = [0; 4];
+        for check in self.0 {
+            let key = &check.key;
+            let (index, status) = match &check.status {
+                Status::Pass => (0, "pass".to_owned()),
+                Status::Fail { expected, actual } => {
+                    (1, format!("FAIL expected {}, got {}", expected, actual))
+                }
+                Status::Missing => (2, "missing".to_owned()),
+                Status::Error(err) => (3, format!("ERROR {}", err)),
+            };
+            counts[index] += 1;
+            writeln!(
+                f,
+                "{} {:>3} {:>4} {:width$} {}",
+                key.year,
+                key.day,
+                key.part.to_string(),
+                key.input,
+                status,
+                width = width
+            )?;
+        }
+        write!(
+            f,
+            "{} passed, {} failed, {} missing, {} errors",
+            counts[0], counts[1], counts[2], counts[3]
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(year: u32, day: u32, part: Part, input: &str) -> Key {
+        Key {
+            year,
+            day,
+            part,
+            input: input.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let answers = Answers::parse(
+            "# year day part input answer\n\n2022 5 1 day5 WJVRLSJJT\n2022 10 2 day10 the screen\n",
+        )
+        .unwrap();
+        let expected = answers.get(&key(2022, 5, Part::One, "day5"));
+        assert_eq!(expected, Some("WJVRLSJJT"));
+        let expected = answers.get(&key(2022, 10, Part::Two, "day10"));
+        assert_eq!(expected, Some("the screen"));
+        assert_eq!(answers.get(&key(2022, 5, Part::Two, "day5")), None);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let error = Answers::parse("2022 1 1 day1 1\n2022 1 3 day1 2").unwrap_err();
+        assert_eq!(error.to_string(), "line 2: unknown part 3, expected 1 or 2");
+
+        let error = Answers::parse("2022 1 1 day1").unwrap_err();
+        assert_eq!(error.to_string(), "line 1: expected an answer");
+
+        let error = Answers::parse("2022 1 1 day1 1\n2022 1 1 day1 2").unwrap_err();
+        assert_eq!(error.to_string(), "line 2: duplicate answer");
+    }
+
+    #[test]
+    fn test_verify() {
+        let registry = Registry::new();
+        let loader = Loader::with_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/resources/test/"));
+        let answers = Answers::parse(
+            "2022 1 1 day1 75622\n2022 1 2 day1 1\n2022 2 1 nope 1\n2021 1 1 day1_2021 1\n",
+        )
+        .unwrap();
+        let checks = verify(&registry, &loader, &answers);
+        let status = |key: &Key| {
+            let check = checks.iter().find(|check| check.key == *key).unwrap();
+            &check.status
+        };
+
+        assert_eq!(status(&key(2022, 1, Part::One, "day1")), &Status::Pass);
+        let fail = Status::Fail {
+            expected: "1".to_owned(),
+            actual: "213159".to_owned(),
+        };
+        assert_eq!(status(&key(2022, 1, Part::Two, "day1")), &fail);
+        assert!(matches!(
+            status(&key(2022, 2, Part::One, "nope")),
+            Status::Error(_)
+        ));
+        assert_eq!(status(&key(2022, 2, Part::Two, "nope")), &Status::Missing);
+        assert_eq!(status(&key(2022, 3, Part::One, "day3")), &Status::Missing);
+        let error = Status::Error("no solution registered".to_owned());
+        assert_eq!(status(&key(2021, 1, Part::One, "day1_2021")), &error);
+        assert!(!is_ok(&checks));
+
+        let report = Report(&checks).to_string();
+        assert!(report.contains("2022   1    1 day1      pass\n"));
+        assert!(report.contains("2022   1    2 day1      FAIL expected 1, got 213159\n"));
+        // every registered day without an answer is missing, so that count isn't fixed
+        let summary = report.lines().last().unwrap();
+        assert!(summary.starts_with("1 passed, 1 failed, "));
+        assert!(summary.ends_with(" missing, 2 errors"));
+    }
+
+    #[test]
+    fn test_manifest() {
+        let loader = Loader::with_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/resources/test/"));
+        let answers = Answers::load(&loader).unwrap();
+        assert_eq!(answers.get(&key(2022, 1, Part::One, "day1")), Some("75622"));
+    }
+}
@@ -0,0 +1,417 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+use std::panic::{self, AssertUnwindSafe};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::solution::{Part, Solver};
+
+/// Where `aoc --bench --save` keeps medians for later runs to compare against.
+pub const BASELINE_FILE: &str = "bench_baseline.txt";
+
+/// A median this much slower than the baseline is a regression...
+const REGRESSION_RATIO: f64 = 1.2;
+/// ...unless it's only this much slower in absolute terms, which is timer noise.
+const REGRESSION_NOISE: Duration = Duration::from_millis(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Stage {
+    Parse,
+    Part1,
+    Part2,
+}
+
+impl Stage {
+    const ALL: [Stage; 3] = [Stage::Parse, Stage::Part1, Stage::Part2];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Stage::Parse => "parse",
+            Stage::Part1 => "part1",
+            Stage::Part2 => "part2",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Stage::ALL.into_iter().find(|stage| stage.name() == name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    pub fn new(mut samples: Vec<Duration>) -> Self {
+        assert!(!samples.is_empty(), "no samples");
+        samples.sort();
+        let middle = samples.len() / 2;
+        // an even number of samples has two middle ones, so take their mean
+        let median = if samples.len().is_multiple_of(2) {
+            (samples[middle - 1] + samples[middle]) / 2
+        } else {
+            samples[middle]
+        };
+        Self {
+            min: samples[0],
+            median,
+            max: samples[samples.len() - 1],
+        }
+    }
+}
+
+/// Timings of one day, each stage measured separately.
+///
+/// A stage that fails is listed in `failures` instead of `stages`, so the
+/// other stages of the day are still reported.
+#[derive(Debug)]
+pub struct Timing {
+    pub year: u32,
+    pub day: u32,
+    pub stages: Vec<(Stage, Stats)>,
+    pub failures: Vec<(Stage, String)>,
+}
+
+/// Parses `input` and answers both parts `runs` times.
+pub fn bench(solver: &dyn Solver, input: &str, runs: usize) -> Timing {
+    let mut samples: [Vec<Duration>; 3] = Default::default();
+    let mut failures: Vec<(Stage, String)> = Vec::new();
+    for _ in 0..runs.max(1) {
+        let parsed = match time(|| solver.parse(input)) {
+            Ok((parsed, elapsed)) => {
+                samples[0].push(elapsed);
+                parsed
+            }
+            Err(err) => {
+                failures.push((Stage::Parse, format!("{:#}", err)));
+                break;
+            }
+        };
+        for (i, part) in [Part::One, Part::Two].into_iter().enumerate() {
+            let stage = Stage::ALL[i + 1];
+            if failures.iter().any(|(failed, _)| *failed == stage) {
+                continue;
+            }
+            match time(|| parsed.solve(part)) {
+                Ok((_, elapsed)) => samples[i + 1].push(elapsed),
+                Err(err) => failures.push((stage, format!("{:#}", err))),
+            }
+        }
+    }
+    let stages = Stage::ALL
+        .into_iter()
+        .zip(samples)
+        .filter(|(stage, samples)| {
+            !samples.is_empty() && !failures.iter().any(|(failed, _)| failed == stage)
+        })
+        .map(|(stage, samples)| (stage, Stats::new(samples)))
+        .collect();
+    Timing {
+        year: solver.year(),
+        day: solver.day(),
+        stages,
+        failures,
+    }
+}
+
+fn time<T>(f: impl FnOnce() -> Result<T>) -> Result<(T, Duration)> {
+    let start = Instant::now();
+    let value =
+        panic::catch_unwind(AssertUnwindSafe(f)).map_err(|_| anyhow!("solver panicked"))??;
+    Ok((value, start.elapsed()))
+}
+
+/// Median per (year, day, stage), one per line: `<year> <day> <stage> <nanoseconds>`.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Baseline {
+    medians: BTreeMap<(u32, u32, Stage), Duration>,
+}
+
+impl Baseline {
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut medians = BTreeMap::new();
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let Some((key, median)) = parse_line(line) else {
+                bail!(
+                    "{}: line {}: expected <year> <day> <stage> <nanoseconds>",
+                    BASELINE_FILE,
+                    i + 1
+                );
+            };
+            medians.insert(key, median);
+        }
+        Ok(Self { medians })
+    }
+
+    /// Adds `timings` on top of what's there, so benchmarking a single day keeps the others.
+    pub fn update(&mut self, timings: &[Timing]) {
+        for timing in timings {
+            for (stage, stats) in timing.stages.iter() {
+                let key = (timing.year, timing.day, *stage);
+                self.medians.insert(key, stats.median);
+            }
+        }
+    }
+
+    pub fn get(&self, year: u32, day: u32, stage: Stage) -> Option<Duration> {
+        self.medians.get(&(year, day, stage)).copied()
+    }
+}
+
+fn parse_line(line: &str) -> Option<((u32, u32, Stage), Duration)> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let [year, day, stage, nanos] = tokens[..] else {
+        return None;
+    };
+    let key = (
+        year.parse().ok()?,
+        day.parse().ok()?,
+        Stage::from_name(stage)?,
+    );
+    Some((key, Duration::from_nanos(nanos.parse().ok()?)))
+}
+
+impl Display for Baseline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for ((year, day, stage), median) in self.medians.iter() {
+            writeln!(f, "{} {} {} {}", year, day, stage.name(), median.as_nanos())?;
+        }
+        Ok(())
+    }
+}
+
+pub fn is_regression(baseline: Duration, median: Duration) -> bool {
+    median > baseline.mul_f64(REGRESSION_RATIO) && median - baseline > REGRESSION_NOISE
+}
+
+/// Table of `timings`, with the change against `baseline` where it has the stage.
+pub struct Report<'a> {
+    pub timings: &'a [Timing],
+    pub baseline: &'a Baseline,
+}
+
+struct Row<'a> {
+    timing: &'a Timing,
+    stage: Stage,
+    stats: Stats,
+    baseline: Option<Duration>,
+}
+
+impl Row<'_> {
+    fn is_regression(&self) -> bool {
+        self.baseline
+            .is_some_and(|baseline| is_regression(baseline, self.stats.median))
+    }
+}
+
+impl Report<'_> {
+    pub fn regressions(&self) -> usize {
+        self.rows().filter(|row| row.is_regression()).count()
+    }
+
+    pub fn failures(&self) -> usize {
+        self.timings
+            .iter()
+            .map(|timing| timing.failures.len())
+            .sum()
+    }
+
+    fn rows(&self) -> impl Iterator<Item = Row<'_>> {
+        self.timings
+            .iter()
+            .flat_map(move |timing| self.rows_of(timing))
+    }
+
+    fn rows_of<'b>(&'b self, timing: &'b Timing) -> impl Iterator<Item = Row<'b>> {
+        timing.stages.iter().map(move |(stage, stats)| Row {
+            timing,
+            stage: *stage,
+            stats: *stats,
+            baseline: self.baseline.get(timing.year, timing.day, *stage),
+        })
+    }
+}
+
+impl Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "year day stage {:>12} {:>12} {:>12}  baseline",
+            "min", "median", "max"
+        )?;
+        for timing in self.timings {
+            for row in self.rows_of(timing) {
+                writeln!(f, "{}", row)?;
+            }
+            for (stage, err) in timing.failures.iter() {
+                writeln!(
+                    f,
+                    "{} {:>3} {:5} error: {}",
+                    timing.year,
+                    timing.day,
+                    stage.name(),
+                    err
+                )?;
+            }
+        }
+        write!(
+            f,
+            "{} regressions, {} failed stages",
+            self.regressions(),
+            self.failures()
+        )
+    }
+}
+
+impl Display for Row<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (timing, stats) = (self.timing, self.stats);
+        let change = match self.baseline {
+            None => "-".to_owned(),
+            Some(baseline) => {
+                let ratio = stats.median.as_secs_f64() / baseline.as_secs_f64();
+                let percent = (ratio - 1.0) * 100.0;
+                let flag = if self.is_regression() {
+                    " REGRESSION"
+                } else {
+                    ""
+                };
+                format!("{:+.0}%{}", percent, flag)
+            }
+        };
+        write!(
+            f,
+            "{} {:>3} {:5} {:>12} {:>12} {:>12}  {}",
+            timing.year,
+            timing.day,
+            self.stage.name(),
+            format!("{:.2?}", stats.min),
+            format!("{:.2?}", stats.median),
+            format!("{:.2?}", stats.max),
+            change
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::Registry;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn test_stats() {
+        let stats = Stats::new(vec![ms(5), ms(1), ms(9), ms(3), ms(4)]);
+        assert_eq!(
+            stats,
+            Stats {
+                min: ms(1),
+                median: ms(4),
+                max: ms(9)
+            }
+        );
+
+        let stats = Stats::new(vec![ms(9), ms(1), ms(3), ms(4)]);
+        assert_eq!(stats.median, Duration::from_micros(3500));
+        assert_eq!(Stats::new(vec![ms(1), ms(9)]).median, ms(5));
+    }
+
+    #[test]
+    fn test_baseline() {
+        let text = "2022 19 parse 1000\n2022 19 part2 5000000000\n";
+        let baseline = Baseline::parse(text).unwrap();
+        assert_eq!(
+            baseline.get(2022, 19, Stage::Part2),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(baseline.get(2022, 19, Stage::Part1), None);
+        assert_eq!(baseline.to_string(), text);
+
+        let error = Baseline::parse("2022 19 solve 1000").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "bench_baseline.txt: line 1: expected <year> <day> <stage> <nanoseconds>"
+        );
+    }
+
+    #[test]
+    fn test_is_regression() {
+        assert!(is_regression(ms(100), ms(130)));
+        assert!(!is_regression(ms(100), ms(110)));
+        assert!(!is_regression(ms(100), ms(50)));
+        // too small to tell apart from noise
+        assert!(!is_regression(
+            Duration::from_micros(10),
+            Duration::from_micros(30)
+        ));
+    }
+
+    #[test]
+    fn test_bench() {
+        let registry = Registry::new();
+        let solver = registry.get(2022, 1).unwrap();
+        let timing = bench(solver, "1000\n2000\n\n4000\n", 3);
+        let stages: Vec<Stage> = timing.stages.iter().map(|(stage, _)| *stage).collect();
+        assert_eq!(stages, Stage::ALL);
+        assert!(timing.failures.is_empty());
+
+        let mut baseline = Baseline::default();
+        baseline.update(&[timing]);
+        assert!(baseline.get(2022, 1, Stage::Parse).is_some());
+
+        let timing = bench(solver, "abc", 3);
+        assert!(timing.stages.is_empty());
+        let failed: Vec<Stage> = timing.failures.iter().map(|(stage, _)| *stage).collect();
+        assert_eq!(failed, [Stage::Parse]);
+    }
+
+    #[test]
+    fn test_bench_failed_part() {
+        let registry = Registry::new();
+        let solver = registry.get(2022, 10).unwrap();
+        let input = crate::util::read_real_data("day10").join("\n");
+        let timing = bench(solver, &input, 2);
+        let stages: Vec<Stage> = timing.stages.iter().map(|(stage, _)| *stage).collect();
+        assert_eq!(stages, [Stage::Parse, Stage::Part1]);
+        assert_eq!(
+            timing.failures,
+            [(
+                Stage::Part2,
+                "day 10: unknown letter at column 1".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_report() {
+        let timing = Timing {
+            year: 2022,
+            day: 24,
+            stages: vec![(Stage::Part1, Stats::new(vec![ms(10), ms(20), ms(30)]))],
+            failures: vec![(Stage::Part2, "day 24: no way out".to_owned())],
+        };
+        let baseline = Baseline::parse("2022 24 part1 10000000").unwrap();
+        let timings = [timing];
+        let report = Report {
+            timings: &timings,
+            baseline: &baseline,
+        };
+        assert_eq!(report.regressions(), 1);
+        assert_eq!(report.failures(), 1);
+        let report = report.to_string();
+        assert!(report
+            .contains("2022  24 part1      10.00ms      20.00ms      30.00ms  +100% REGRESSION"));
+        assert!(report.contains("2022  24 part2 error: day 24: no way out"));
+        assert!(report.ends_with("1 regressions, 1 failed stages"));
+    }
+}
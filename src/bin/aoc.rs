@@ -0,0 +1,180 @@
+use std::path::Path;
+use std::process::ExitCode;
+use std::{env, fs};
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use aoc2022::answers::{self, Answers, Report};
+use aoc2022::bench::{self, Baseline, BASELINE_FILE};
+use aoc2022::input::{self, Loader};
+use aoc2022::registry::{input_name, Registry};
+use aoc2022::solution::{self, Part, Solver};
+
+const USAGE: &str = "usage:
+    aoc <year> <day> <part> <input>   run one part, <input> is a file path or - for stdin
+    aoc --all [dir]                   run every registered day on <dir>/<day input>,
+                                      <dir> defaults to $AOC_INPUT_DIR or resources/test/,
+                                      days without an input file are reported as missing
+    aoc --verify [dir]                check answers against <dir>/answers.txt
+    aoc --bench [--runs <n>] [--save] [<year> <day>]
+                                      time parse, part 1 and part 2 of every day (or one day)
+                                      and compare medians with ./bench_baseline.txt,
+                                      a stage that fails is reported without its timing,
+                                      --save writes this run's medians there";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("error: {:#}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<bool> {
+    let registry = Registry::new();
+    match args {
+        [flag] | [flag, _] if flag == "--all" => {
+            let loader = match args.get(1) {
+                Some(dir) => Loader::with_dir(dir),
+                None => Loader::new(),
+            };
+            Ok(run_all(&registry, &loader))
+        }
+        [flag] | [flag, _] if flag == "--verify" => {
+            let loader = match args.get(1) {
+                Some(dir) => Loader::with_dir(dir),
+                None => Loader::new(),
+            };
+            let answers = Answers::load(&loader)?;
+            let checks = answers::verify(&registry, &loader, &answers);
+            println!("{}", Report(&checks));
+            Ok(answers::is_ok(&checks))
+        }
+        [flag, rest @ ..] if flag == "--bench" => run_bench(&registry, rest),
+        [year, day, part, input] => {
+            let solver = find_solver(&registry, year, day)?;
+            let part: Part = part.parse().map_err(|err: String| anyhow!(err))?;
+            let input = input::load_path_or_stdin(input)?;
+            let answer = solution::solve(solver, input.raw(), &[part])?;
+            println!("{}", answer[0]);
+            Ok(true)
+        }
+        _ => bail!("{}", USAGE),
+    }
+}
+
+fn find_solver<'a>(registry: &'a Registry, year: &str, day: &str) -> Result<&'a dyn Solver> {
+    let year = year
+        .parse()
+        .with_context(|| format!("invalid year {}", year))?;
+    let day = day
+        .parse()
+        .with_context(|| format!("invalid day {}", day))?;
+    registry
+        .get(year, day)
+        .ok_or_else(|| anyhow!("no solution for {} day {}", year, day))
+}
+
+fn run_bench(registry: &Registry, args: &[String]) -> Result<bool> {
+    let mut runs = 5;
+    let mut save = false;
+    let mut days = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--runs" => {
+                let value = args.next().ok_or_else(|| anyhow!("{}", USAGE))?;
+                runs = value
+                    .parse()
+                    .with_context(|| format!("invalid number of runs {}", value))?;
+            }
+            "--save" => save = true,
+            _ => days.push(arg),
+        }
+    }
+    let solvers: Vec<&dyn Solver> = match days[..] {
+        [] => registry.iter().collect(),
+        [year, day] => vec![find_solver(registry, year, day)?],
+        _ => bail!("{}", USAGE),
+    };
+
+    let path = Path::new(BASELINE_FILE);
+    let mut baseline = if path.exists() {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        Baseline::parse(&text)?
+    } else {
+        Baseline::default()
+    };
+
+    let loader = Loader::new();
+    let mut ok = true;
+    let mut timings = Vec::new();
+    for solver in solvers {
+        let (year, day) = (solver.year(), solver.day());
+        let name = input_name(year, day);
+        if !loader.dir().join(&name).exists() {
+            eprintln!("{} day {:>2}: missing input {}", year, day, name);
+            continue;
+        }
+        match loader.load(&name) {
+            Ok(input) => timings.push(bench::bench(solver, input.raw(), runs)),
+            Err(err) => {
+                ok = false;
+                eprintln!("{} day {:>2}: error: {:#}", year, day, err);
+            }
+        }
+    }
+
+    let report = bench::Report {
+        timings: &timings,
+        baseline: &baseline,
+    };
+    println!("{}", report);
+    ok &= report.regressions() == 0 && report.failures() == 0;
+    if save {
+        baseline.update(&timings);
+        fs::write(path, baseline.to_string())
+            .with_context(|| format!("failed to write {}", path.display()))?;
+    }
+    Ok(ok)
+}
+
+fn run_all(registry: &Registry, loader: &Loader) -> bool {
+    let mut ok = true;
+    for solver in registry.iter() {
+        let (year, day) = (solver.year(), solver.day());
+        let name = input_name(year, day);
+        // days without an input of their own aren't failures, there's just nothing to run
+        if !loader.dir().join(&name).exists() {
+            println!("{} day {:>2}: missing input {}", year, day, name);
+            continue;
+        }
+        let parts = [Part::One, Part::Two];
+        let result = loader
+            .load(&name)
+            .and_then(|input| solution::solve_each(solver, input.raw(), &parts));
+        let answers = match result {
+            Ok(answers) => answers,
+            Err(err) => {
+                ok = false;
+                println!("{} day {:>2}: error: {:#}", year, day, err);
+                continue;
+            }
+        };
+        for (part, answer) in parts.iter().zip(answers) {
+            match answer {
+                Ok(answer) => println!("{} day {:>2} part {}: {}", year, day, part, answer),
+                Err(err) => {
+                    ok = false;
+                    println!("{} day {:>2} part {}: error: {:#}", year, day, part, err);
+                }
+            }
+        }
+    }
+    ok
+}
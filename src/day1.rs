@@ -1,39 +1,85 @@
-pub fn day1<S: AsRef<str>>(input: &Vec<S>) -> (usize, u32) {
-    let calories_per_elf = calories_per_elf(input);
+use anyhow::{anyhow, Result};
 
-    let result = calories_per_elf
+use crate::error::{ParseError, Scanner};
+use crate::solution::Solution;
+
+pub fn day1<S: AsRef<str>>(input: &[S]) -> Result<(usize, u32)> {
+    Ok(most_calories(&calories_per_elf(input)?))
+}
+
+// the elf counting from 1 and what it carries
+fn most_calories(calories_per_elf: &[u32]) -> (usize, u32) {
+    calories_per_elf
         .iter()
         .enumerate()
         .max_by(|(_, x), (_, y)| x.cmp(y))
         .map(|(index, calories)| (index + 1, calories.to_owned()))
-        .expect("day1: unexpected empty input");
-
-    result
+        .expect("calories_per_elf always has at least one elf")
 }
 
-fn calories_per_elf<S: AsRef<str>>(input: &Vec<S>) -> Vec<u32> {
+fn calories_per_elf<S: AsRef<str>>(input: &[S]) -> Result<Vec<u32>, ParseError> {
     let mut calories_per_elf = Vec::new();
-    let mut current = 0;
-    for line in input {
+    let mut current: u32 = 0;
+    for (i, line) in input.iter().enumerate() {
         let line = line.as_ref();
         if line.is_empty() {
             calories_per_elf.push(current);
             current = 0;
             continue;
         }
-        let calories: u32 = line
-            .parse()
-            .unwrap_or_else(|_| panic!("failed to parse {}", line));
-        current += calories;
+        let mut scanner = Scanner::new(Day1::DAY, i, line);
+        let at_calories = scanner.clone();
+        let calories: u32 = scanner.number()?;
+        scanner.end()?;
+        current = current
+            .checked_add(calories)
+            .ok_or_else(|| at_calories.error(&format!("at most {} calories per elf", u32::MAX)))?;
     }
     calories_per_elf.push(current);
-    calories_per_elf
+    Ok(calories_per_elf)
+}
+
+pub fn day1_part2<S: AsRef<str>>(input: &[S]) -> Result<u32> {
+    top_three(&calories_per_elf(input)?)
 }
 
-pub fn day1_part2<S: AsRef<str>>(input: &Vec<S>) -> u32 {
-    let mut calories_per_elf = calories_per_elf(input);
+fn top_three(calories_per_elf: &[u32]) -> Result<u32> {
+    let mut calories_per_elf = calories_per_elf.to_vec();
     calories_per_elf.sort_by(|x, y| y.cmp(x));
-    calories_per_elf.iter().take(3).sum()
+    calories_per_elf
+        .iter()
+        .take(3)
+        .try_fold(0u32, |sum, calories| sum.checked_add(*calories))
+        .ok_or_else(|| {
+            anyhow!(
+                "day 1: the top three elves carry more than {} calories",
+                u32::MAX
+            )
+        })
+}
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    const YEAR: u32 = 2022;
+    const DAY: u32 = 1;
+
+    type Input = Vec<u32>;
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let input: Vec<&str> = input.lines().collect();
+        Ok(calories_per_elf(&input)?)
+    }
+
+    fn part1(calories_per_elf: &Self::Input) -> Result<u32> {
+        Ok(most_calories(calories_per_elf).1)
+    }
+
+    fn part2(calories_per_elf: &Self::Input) -> Result<u32> {
+        top_three(calories_per_elf)
+    }
 }
 
 #[cfg(test)]
@@ -43,33 +89,55 @@ mod tests {
 
     #[test]
     fn test_day1() {
-        let actual = day1(&vec![
+        let actual = day1(&[
             "1000", "2000", "3000", "", "4000", "", "5000", "6000", "", "7000", "8000", "9000", "",
             "10000",
         ]);
 
-        assert_eq!(actual, (4, 24000));
+        assert_eq!(actual.unwrap(), (4, 24000));
     }
 
     #[test]
     fn test_with_real_data() {
-        let (index, value) = day1(&read_real_data("day1"));
+        let (index, value) = day1(&read_real_data("day1")).unwrap();
         assert_eq!((index, value), (137, 75622));
     }
 
     #[test]
     fn test_part2() {
-        let actual = day1_part2(&vec![
+        let actual = day1_part2(&[
             "1000", "2000", "3000", "", "4000", "", "5000", "6000", "", "7000", "8000", "9000", "",
             "10000",
         ]);
-        assert_eq!(actual, 45000);
+        assert_eq!(actual.unwrap(), 45000);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let error = day1(&["1000", "", "10OO"]).unwrap_err();
+        let error = error.downcast_ref::<ParseError>().unwrap();
+        assert_eq!((error.line, error.column), (3, 3));
+        assert_eq!(error.found, "OO");
+    }
+
+    #[test]
+    fn test_overflow() {
+        let error = day1(&["4000000000", "300000000", ""]).unwrap_err();
+        let error = error.downcast_ref::<ParseError>().unwrap();
+        assert_eq!((error.line, error.column), (2, 1));
+        assert_eq!(error.expected, "at most 4294967295 calories per elf");
+
+        let error = day1_part2(&["4000000000", "", "300000000"]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 1: the top three elves carry more than 4294967295 calories"
+        );
     }
 
     #[test]
     fn test_part2_with_real_data() {
         let expected = 213159;
-        let actual = day1_part2(&read_real_data("day1"));
+        let actual = day1_part2(&read_real_data("day1")).unwrap();
         assert_eq!(actual, expected, "expected {}, got {}", expected, actual);
     }
 }
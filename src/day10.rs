@@ -1,3 +1,10 @@
+use anyhow::{anyhow, Result};
+
+use crate::error::{ParseError, Scanner};
+use crate::grid::Grid;
+use crate::ocr;
+use crate::solution::Solution;
+
 const CHECKPOINTS: [usize; 6] = [20, 60, 100, 140, 180, 220];
 
 pub struct Counter {
@@ -27,76 +34,161 @@ impl Counter {
     pub fn result(&self) -> i32 {
         let mut result = 0;
         for i in CHECKPOINTS {
-            result += (i as i32) * self.xs[i];
+            result += (i as i32) * self.get(i);
         }
         result
     }
 
+    // x keeps its last value once the program is over
     fn get(&self, i: usize) -> i32 {
-        self.xs[i]
+        self.xs.get(i).copied().unwrap_or(self.x)
     }
 }
 
-pub fn solve(input: &[String]) -> Counter {
+pub fn solve(input: &[String]) -> Result<Counter> {
     let mut counter = Counter::new();
-    for line in input.iter() {
+    for (i, line) in input.iter().enumerate() {
         if line == "noop" {
             counter.tick();
             continue;
         }
-        let y: i32 = line.split(' ').nth(1).unwrap().parse().unwrap();
+        let y: i32 = parse_addx(i, line)?;
         counter.tick();
         counter.tick();
 
         counter.add(y);
     }
-    counter
+    Ok(counter)
+}
+
+fn parse_addx(i: usize, line: &str) -> Result<i32, ParseError> {
+    let mut scanner = Scanner::new(Day10::DAY, i, line);
+    scanner
+        .tag("addx ")
+        .map_err(|_| scanner.error("\"noop\" or \"addx\""))?;
+    let result = scanner.number()?;
+    scanner.end()?;
+    Ok(result)
 }
 
-pub fn part2(counter: &Counter) {
-    let width = 40;
-    let height = 6;
-    let mut screen = Vec::new();
-    for _ in 0..height {
-        let line = vec!['.'; width];
-        screen.push(line);
-    }
-    for i in 0..width * height {
-        let x = counter.get(i + 1);
-        let (row, col) = (i / width, i % width);
-        if x - 1 == col as i32 || x == col as i32 || x + 1 == col as i32 {
-            screen[row][col] = '#';
+const WIDTH: usize = 40;
+const HEIGHT: usize = 6;
+
+pub fn screen(counter: &Counter) -> Grid<char> {
+    let mut screen = Grid::new(WIDTH, HEIGHT, '.');
+    for pos in screen.positions().collect::<Vec<_>>() {
+        let x = counter.get(pos.row as usize * WIDTH + pos.col as usize + 1);
+        if (x - pos.col).abs() <= 1 {
+            screen[pos] = '#';
         }
     }
-    for line in screen.iter().take(height) {
-        for cell in line.iter().take(width) {
-            print!("{}", cell);
-        }
-        println!();
+    screen
+}
+
+pub fn part2(counter: &Counter) -> Result<String> {
+    ocr::read(&screen(counter)).map_err(|err| anyhow!("day 10: {}", err))
+}
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    const YEAR: u32 = 2022;
+    const DAY: u32 = 10;
+
+    type Input = Counter;
+    type Part1 = i32;
+    type Part2 = String;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let input: Vec<String> = input.lines().map(|line| line.to_owned()).collect();
+        solve(&input)
+    }
+
+    fn part1(counter: &Counter) -> Result<i32> {
+        Ok(counter.result())
+    }
+
+    fn part2(counter: &Counter) -> Result<String> {
+        part2(counter)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::solution::{self, Erased, Part};
     use crate::util;
 
     #[test]
     fn test_solve() {
         let input = util::read_real_data("day10");
-        assert_eq!(solve(&input).result(), 13140);
+        assert_eq!(solve(&input).unwrap().result(), 13140);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let input = ["noop".to_owned(), "addx 1".to_owned(), "addy 3".to_owned()];
+        let error = solve(&input).err().unwrap();
+        assert_eq!(
+            error.downcast::<ParseError>().unwrap(),
+            ParseError::new(10, 3, 1, "\"noop\" or \"addx\"", "addy")
+        );
+    }
+
+    #[test]
+    fn test_short_program() {
+        let input = ["addx 2".to_owned()];
+        assert_eq!(
+            solve(&input).unwrap().result(),
+            3 * (20 + 60 + 100 + 140 + 180 + 220)
+        );
     }
 
     #[test]
     fn test_solve_with_real_data() {
         let input = util::read_real_data("day10_big");
-        assert_eq!(solve(&input).result(), 14520);
+        assert_eq!(solve(&input).unwrap().result(), 14520);
     }
 
     #[test]
     fn test_part2_with_real_data() {
         let input = util::read_real_data("day10_big");
-        let counter = solve(&input);
-        part2(&counter);
+        let counter = solve(&input).unwrap();
+        assert_eq!(part2(&counter).unwrap(), "PZBGZEJB");
+    }
+
+    #[test]
+    fn test_unreadable_screen() {
+        // the example draws stripes rather than letters, which only fails part 2
+        let input = util::read_real_data("day10").join("\n");
+        let parts = [Part::One, Part::Two];
+        let answers = solution::solve_each(&Erased::<Day10>::new(), &input, &parts).unwrap();
+        assert_eq!(answers[0].as_ref().unwrap(), "13140");
+        assert_eq!(
+            answers[1].as_ref().unwrap_err().to_string(),
+            "day 10: unknown letter at column 1"
+        );
+    }
+
+    #[test]
+    fn test_screen() {
+        let input = util::read_real_data("day10");
+        let screen = screen(&solve(&input).unwrap());
+        assert_eq!(
+            screen.to_string(),
+            [
+                "##..##..##..##..##..##..##..##..##..##..",
+                "###...###...###...###...###...###...###.",
+                "####....####....####....####....####....",
+                "#####.....#####.....#####.....#####.....",
+                "######......######......######......####",
+                "#######.......#######.......#######.....",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            part2(&solve(&input).unwrap()).unwrap_err().to_string(),
+            "day 10: unknown letter at column 1"
+        );
     }
 }
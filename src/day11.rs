@@ -1,7 +1,13 @@
 use std::{cell::RefCell, collections::VecDeque, rc::Rc};
 
+use anyhow::Result;
+
+use crate::error::{ParseError, Scanner};
+use crate::solution::Solution;
+
 type Worry = u64;
 
+#[derive(Clone)]
 struct Monkey {
     items: VecDeque<Worry>,
     operation: Operation,
@@ -12,25 +18,35 @@ struct Monkey {
 }
 
 impl Monkey {
-    fn parse(input: &[&str]) -> Self {
-        let test_false_target = last_u32(input[5]) as usize;
-        let test_true_target = last_u32(input[4]) as usize;
-        let operation = Operation::parse(input[2]);
-        let test_delimiter = last_u32(input[3]);
-
-        let items = input[1]["  Starting items: ".len()..input[1].len()]
-            .split(", ")
-            .map(|item| item.parse().unwrap())
-            .collect();
+    fn parse(input: &[Scanner], monkeys: usize) -> Result<Self, ParseError> {
+        let mut header = input[0].clone();
+        header.tag("Monkey ")?;
+        header.number::<usize>()?;
+        header.tag(":")?;
+        header.end()?;
 
-        Monkey {
+        let mut starting = input[1].clone();
+        starting.tag("  Starting items: ")?;
+        let mut items = VecDeque::from([starting.number()?]);
+        while !starting.is_empty() {
+            starting.tag(", ")?;
+            items.push_back(starting.number()?);
+        }
+
+        let operation = Operation::parse(input[2].clone())?;
+        let test_delimiter = divider(input[3].clone(), "  Test: divisible by ")?;
+        let test_true_target = target(input[4].clone(), "    If true: throw to monkey ", monkeys)?;
+        let test_false_target =
+            target(input[5].clone(), "    If false: throw to monkey ", monkeys)?;
+
+        Ok(Monkey {
             items,
             operation,
             divider: test_delimiter,
             test_true_target,
             test_false_target,
             count: 0,
-        }
+        })
     }
 
     fn accept_item(&mut self, worry: Worry) {
@@ -43,7 +59,7 @@ impl Monkey {
     }
 
     fn receiver(&self, worry: Worry) -> usize {
-        if worry % (self.divider as Worry) == 0 {
+        if worry.is_multiple_of(self.divider as Worry) {
             self.test_true_target
         } else {
             self.test_false_target
@@ -51,10 +67,29 @@ impl Monkey {
     }
 }
 
-fn last_u32(line: &str) -> u32 {
-    line.split(' ').last().unwrap().parse().unwrap()
+fn divider(mut line: Scanner, prefix: &str) -> Result<u32, ParseError> {
+    line.tag(prefix)?;
+    let at_divider = line.clone();
+    let result = line.number()?;
+    line.end()?;
+    if result == 0 {
+        return Err(at_divider.error("a divisor above 0"));
+    }
+    Ok(result)
+}
+
+fn target(mut line: Scanner, prefix: &str, monkeys: usize) -> Result<usize, ParseError> {
+    line.tag(prefix)?;
+    let at_target = line.clone();
+    let result = line.number()?;
+    line.end()?;
+    if result >= monkeys {
+        return Err(at_target.error(&format!("a monkey from 0 to {}", monkeys - 1)));
+    }
+    Ok(result)
 }
 
+#[derive(Clone)]
 enum Operation {
     Mul(Worry),
     MulOld,
@@ -62,18 +97,19 @@ enum Operation {
 }
 
 impl Operation {
-    fn parse(line: &str) -> Self {
-        if line.ends_with("* old") {
-            return Operation::MulOld;
+    fn parse(mut line: Scanner) -> Result<Self, ParseError> {
+        line.tag("  Operation: new = old ")?;
+        let operation = line.char_map("* or +", |c| Some(c).filter(|c| "*+".contains(*c)))?;
+        line.tag(" ")?;
+        if operation == '*' && line.rest() == "old" {
+            return Ok(Operation::MulOld);
         }
 
-        let mut tokens = line.split(' ').rev();
-        let val = tokens.next().unwrap().parse().unwrap();
-        let operation = tokens.next().unwrap();
+        let val = line.number()?;
+        line.end()?;
         match operation {
-            "*" => Operation::Mul(val),
-            "+" => Operation::Plus(val),
-            _ => panic!("unknown operation {}", val),
+            '*' => Ok(Operation::Mul(val)),
+            _ => Ok(Operation::Plus(val)),
         }
     }
 
@@ -86,37 +122,54 @@ impl Operation {
     }
 }
 
-struct Monkeys {
+pub struct Monkeys {
     monkeys: Vec<Rc<RefCell<Monkey>>>,
-    cooldown: u32,
     base: u32,
 }
 
+// every monkey is copied, so playing rounds on the copy leaves the original as it was
+impl Clone for Monkeys {
+    fn clone(&self) -> Self {
+        let copy = |monkey: &Rc<RefCell<Monkey>>| Rc::new(RefCell::new(monkey.borrow().clone()));
+        Self {
+            monkeys: self.monkeys.iter().map(copy).collect(),
+            base: self.base,
+        }
+    }
+}
+
 impl Monkeys {
-    fn parse_monkeys(input: &[&str], cooldown: u32) -> Self {
-	let block_size = 6;
-        let input: Vec<&str> = input.iter().filter(|line| !line.is_empty()).copied().collect();
+    fn parse_monkeys(input: &[&str]) -> Result<Self, ParseError> {
+        let block_size = 6;
+        let input: Vec<Scanner> = input
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| !line.is_empty())
+            .map(|(i, line)| Scanner::new(Day11::DAY, i, line))
+            .collect();
+        let count = input.len() / block_size;
+        if count < 2 {
+            let found = format!("{} monkeys", count);
+            return Err(ParseError::new(Day11::DAY, 1, 1, "at least two monkeys", &found));
+        }
         let mut monkeys = Vec::new();
-	let blocks = input.len()/6;
-        for i in 0..blocks {
-            let part: Vec<&str> = input[i * block_size..i * block_size + block_size].to_vec();
-            let monkey = Monkey::parse(&part);
+        for part in input.chunks(block_size) {
+            if part.len() < block_size {
+                return Err(part[part.len() - 1].error("a complete monkey description"));
+            }
+            let monkey = Monkey::parse(part, count)?;
             monkeys.push(Rc::new(RefCell::new(monkey)));
         }
         let base = monkeys.iter().map(|monkey| monkey.borrow().divider).product();
-        Self {
-            monkeys,
-            cooldown,
-            base,
-        }
+        Ok(Self { monkeys, base })
     }
 
-    fn turn(&mut self) {
+    fn turn(&mut self, cooldown: u32) {
         for monkey in self.monkeys.iter() {
             let mut monkey = monkey.borrow_mut();
             while let Some(worry) = monkey.items.pop_front() {
                 let worry =
-                    (monkey.update_worry(worry) / (self.cooldown as Worry)) % (self.base as Worry);
+                    (monkey.update_worry(worry) / (cooldown as Worry)) % (self.base as Worry);
                 let receiver = monkey.receiver(worry);
 		let mut receiver = self.monkeys[receiver].borrow_mut();
 		receiver.accept_item(worry);
@@ -131,15 +184,44 @@ impl Monkeys {
     }
 }
 
-pub fn solve(input: &[&str], rounds: u32, cooldown: u32) -> u128 {
-    let mut monkeys = Monkeys::parse_monkeys(input, cooldown);
+pub fn solve(input: &[&str], rounds: u32, cooldown: u32) -> Result<u128> {
+    let monkeys = Monkeys::parse_monkeys(input)?;
+    Ok(play(&monkeys, rounds, cooldown))
+}
+
+fn play(monkeys: &Monkeys, rounds: u32, cooldown: u32) -> u128 {
+    let mut monkeys = monkeys.clone();
     for _ in 0..rounds {
-        monkeys.turn();
+        monkeys.turn(cooldown);
     }
 
     monkeys.monkey_business()
 }
 
+pub struct Day11;
+
+impl Solution for Day11 {
+    const YEAR: u32 = 2022;
+    const DAY: u32 = 11;
+
+    type Input = Monkeys;
+    type Part1 = u128;
+    type Part2 = u128;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let input: Vec<&str> = input.lines().collect();
+        Ok(Monkeys::parse_monkeys(&input)?)
+    }
+
+    fn part1(monkeys: &Self::Input) -> Result<u128> {
+        Ok(play(monkeys, 20, 3))
+    }
+
+    fn part2(monkeys: &Self::Input) -> Result<u128> {
+        Ok(play(monkeys, 10000, 1))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -176,7 +258,7 @@ mod tests {
             "    If true: throw to monkey 0",
             "    If false: throw to monkey 1",
         ];
-        assert_eq!(solve(&input, 10000, 1), 2713310158);
+        assert_eq!(solve(&input, 10000, 1).unwrap(), 2713310158);
     }
 
     #[test]
@@ -210,20 +292,58 @@ mod tests {
             "    If true: throw to monkey 0",
             "    If false: throw to monkey 1",
         ];
-        assert_eq!(solve(&input, 20, 3), 10605);
+        assert_eq!(solve(&input, 20, 3).unwrap(), 10605);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let mut input = vec![
+            "Monkey 0:",
+            "  Starting items: 79, 98",
+            "  Operation: new = old * 19",
+            "  Test: divisible by 23",
+            "    If true: throw to monkey 1",
+            "    If false: throw to monkey 1",
+            "",
+            "Monkey 1:",
+            "  Starting items: 54, 65",
+            "  Operation: new = old - 6",
+            "  Test: divisible by 19",
+            "    If true: throw to monkey 2",
+            "    If false: throw to monkey 0",
+        ];
+        let error = solve(&input, 20, 3).unwrap_err();
+        assert_eq!(
+            error.downcast::<ParseError>().unwrap(),
+            ParseError::new(11, 10, 24, "* or +", "-")
+        );
+
+        input[9] = "  Operation: new = old + 6";
+        let error = solve(&input, 20, 3).unwrap_err();
+        assert_eq!(
+            error.downcast::<ParseError>().unwrap(),
+            ParseError::new(11, 12, 30, "a monkey from 0 to 1", "2")
+        );
+
+        input[10] = "  Test: divisible by 0";
+        let error = solve(&input, 20, 3).unwrap_err();
+        assert_eq!(
+            error.downcast::<ParseError>().unwrap(),
+            ParseError::new(11, 11, 22, "a divisor above 0", "0")
+        );
     }
 
     #[test]
     fn test_solve_with_real_data() {
         let data = util::read_real_data("day11");
         let data: Vec<&str> = data.iter().map(|line| line.as_str()).collect();
-        assert_eq!(solve(&data, 20, 3), 54752);
+        assert_eq!(solve(&data, 20, 3).unwrap(), 54752);
     }
 
     #[test]
     fn test_solve_part2() {
         let data = util::read_real_data("day11");
         let data: Vec<&str> = data.iter().map(|line| line.as_str()).collect();
-        assert_eq!(solve(&data, 10000, 1), 13606755504);
+        assert_eq!(solve(&data, 10000, 1).unwrap(), 13606755504);
     }
 }
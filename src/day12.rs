@@ -1,171 +1,185 @@
-use std::collections::{HashMap, HashSet};
+use anyhow::{anyhow, Result};
 
-type Point = (usize, usize);
+use crate::error::ParseError;
+use crate::grid::{Grid, Pos};
+use crate::search;
+use crate::solution::Solution;
 
-struct Grid {
-    grid: Vec<Vec<char>>,
-    width: usize,
-    height: usize,
-    start: Point,
-    end: Point,
+pub struct HeightMap {
+    grid: Grid<char>,
+    start: Pos,
+    end: Pos,
 }
 
-impl Grid {
-    fn new(grid: Vec<Vec<char>>) -> Self {
-        let height = grid.len();
-        let width = grid[0].len();
-        let (start, end) = find_start(&grid);
-        Self {
-            grid,
-            width,
-            height,
-            start,
-            end,
-        }
+impl HeightMap {
+    pub fn new(input: &[&str]) -> Result<Self, ParseError> {
+        let grid = Grid::parse(Day12::DAY, input, "a-z, S or E", |c| {
+            matches!(c, 'a'..='z' | 'S' | 'E').then_some(c)
+        })?;
+        let missing =
+            |expected| ParseError::new(Day12::DAY, input.len() + 1, 1, expected, "end of input");
+        let start = grid.find(|c| *c == 'S').ok_or_else(|| missing("S"))?;
+        let end = grid.find(|c| *c == 'E').ok_or_else(|| missing("E"))?;
+        Ok(Self { grid, start, end })
     }
 
-    fn solve(&self) -> u32 {
-        let mut memo = HashMap::new();
-        let mut visited = HashSet::new();
-        self.shortest_path(self.end, &mut memo, &mut visited)
-            .expect("no path found")
+    /// Shortest route from S to E.
+    pub fn route(&self) -> Result<Vec<Pos>> {
+        self.route_down(|pos| pos == self.start)
+            .ok_or_else(|| anyhow!("day {}: no path from S to E", Day12::DAY))
     }
 
-    fn shortest_path(
-        &self,
-        current: Point,
-        memo: &mut HashMap<Point, Option<u32>>,
-        visited: &mut HashSet<Point>,
-    ) -> Option<u32> {
-        if current == self.start {
-            return Some(0);
-        }
-        visited.insert(current);
-
-        let mut candidates = Vec::new();
-        for point in self.not_visited_adjacent(visited, current) {
-            if !self.can_jump(point, current) {
-                continue;
-            }
-            if let Some(path_length) = self.calc_shortest(memo, visited, point) {
-                candidates.push(path_length);
-            }
-        }
-        candidates.iter().min().map(|result| result + 1)
-    }
-
-    fn not_visited_adjacent(&self, visited: &HashSet<Point>, current: Point) -> Vec<Point> {
-        let adjacent = self.adjacent(current);
-        adjacent
-            .iter()
-            .filter(|point| !visited.contains(point))
-            .map(|(col, row)| (*col, *row))
-            .collect()
-    }
-
-    fn calc_shortest(
-        &self,
-        memo: &mut HashMap<Point, Option<u32>>,
-        visited: &mut HashSet<Point>,
-        point: Point,
-    ) -> Option<u32> {
-        let mut result = None;
-        let maybe_path_length = memo.get(&point);
-        if maybe_path_length.is_none() {
-            result = self.shortest_path(point, memo, visited);
-            memo.insert(point, result);
-        }
-        result
+    /// Shortest route to E from any square at elevation a.
+    pub fn scenic_route(&self) -> Result<Vec<Pos>> {
+        self.route_down(|pos| height(self.grid[pos]) == height('a'))
+            .ok_or_else(|| anyhow!("day {}: no path from elevation a to E", Day12::DAY))
     }
 
-    fn at(&self, (col, row): Point) -> char {
-        self.grid[row][col]
+    // searches backwards from E, so every square matching `is_start` is tried at once
+    fn route_down(&self, is_start: impl Fn(Pos) -> bool) -> Option<Vec<Pos>> {
+        let successors = |current: &Pos| -> Vec<Pos> {
+            self.grid
+                .neighbours4(*current)
+                .filter(|point| self.can_jump(*point, *current))
+                .collect()
+        };
+        let path = search::bfs([self.end], successors, |point| is_start(*point))?;
+        let mut route = path.states;
+        route.reverse();
+        Some(route)
     }
 
-    fn can_jump(&self, from: Point, to: Point) -> bool {
-        let from = self.at(from);
-        let to = self.at(to);
-        can_jump(from, to)
+    fn can_jump(&self, from: Pos, to: Pos) -> bool {
+        can_jump(self.grid[from], self.grid[to])
     }
 
-    fn adjacent(&self, (col, row): Point) -> Vec<Point> {
-        let (col, row) = (col as i32, row as i32);
-        let result = [
-            (col, row + 1),
-            (col + 1, row),
-            (col, row - 1),
-            (col - 1, row),
-        ];
-
-        result
-            .iter()
-            .filter(|(col, row)| self.fits_grid((col, row)))
-            .map(|(col, row)| (*col as usize, *row as usize))
-            .collect()
-    }
-
-    fn fits_grid(&self, (col, row): (&i32, &i32)) -> bool {
-        *col >= 0 && *row >= 0 && *col < self.width as i32 && *row < self.height as i32
+    /// The map with `route` drawn on it the way the puzzle does.
+    pub fn draw(&self, route: &[Pos]) -> Grid<char> {
+        let mut map = Grid::new(self.grid.width(), self.grid.height(), '.');
+        for step in route.windows(2) {
+            map[step[0]] = match step[1] - step[0] {
+                Pos::UP => '^',
+                Pos::DOWN => 'v',
+                Pos::LEFT => '<',
+                _ => '>',
+            };
+        }
+        if let Some(last) = route.last() {
+            map[*last] = self.grid[*last];
+        }
+        map
     }
 }
 
-fn add1_char(c: char) -> char {
-    std::char::from_u32(c as u32 + 1).unwrap()
+fn height(c: char) -> u32 {
+    match c {
+        'S' => 'a' as u32,
+        'E' => 'z' as u32,
+        _ => c as u32,
+    }
 }
 
+// at most one step up, any number of steps down
 fn can_jump(from: char, to: char) -> bool {
-    if from == 'S' || from == to {
-        return true;
-    };
-    if to == 'E' {
-        return from == 'z' || from == 'y';
-    }
+    height(to) <= height(from) + 1
+}
 
-    add1_char(from) == to
+pub fn solve(input: &[&str]) -> Result<u32> {
+    Day12::part1(&HeightMap::new(input)?)
 }
 
-pub fn solve(input: Vec<Vec<char>>) -> u32 {
-    let grid = Grid::new(input);
-    grid.solve()
+pub fn part2(input: &[&str]) -> Result<u32> {
+    Day12::part2(&HeightMap::new(input)?)
 }
 
-fn find_start(input: &[Vec<char>]) -> (Point, Point) {
-    let mut start = (0, 0);
-    let mut end = (0, 0);
-    for (row, line) in input.iter().enumerate() {
-        for col in 0..line.len() {
-            if input[row][col] == 'S' {
-                start = (col, row);
-            } else if input[row][col] == 'E' {
-                end = (col, row);
-            }
-        }
+pub struct Day12;
+
+impl Solution for Day12 {
+    const YEAR: u32 = 2022;
+    const DAY: u32 = 12;
+
+    type Input = HeightMap;
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let input: Vec<&str> = input.lines().collect();
+        Ok(HeightMap::new(&input)?)
+    }
+
+    fn part1(map: &Self::Input) -> Result<u32> {
+        Ok(map.route()?.len() as u32 - 1)
+    }
+
+    fn part2(map: &Self::Input) -> Result<u32> {
+        Ok(map.scenic_route()?.len() as u32 - 1)
     }
-    (start, end)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    // use crate::util;
+    use crate::util;
 
     #[test]
     fn test_solve() {
         let input = ["Sabqponm", "abcryxxl", "accszExk", "acctuvwj", "abdefghi"];
+        assert_eq!(solve(&input).unwrap(), 31);
+    }
 
-        let input = input.iter().map(|line| line.chars().collect()).collect();
-        assert_eq!(solve(input), 31);
+    #[test]
+    fn test_parse_error() {
+        let input = ["Sabqponm", "abcryxxl", "acc1zExk"];
+        let error = solve(&input).unwrap_err();
+        assert_eq!(
+            error.downcast::<ParseError>().unwrap(),
+            ParseError::new(12, 3, 4, "a-z, S or E", "1zExk")
+        );
+
+        let input = ["Sabqponm", "abcryxxl"];
+        let error = solve(&input).unwrap_err();
+        assert_eq!(
+            error.downcast::<ParseError>().unwrap(),
+            ParseError::new(12, 3, 1, "E", "end of input")
+        );
     }
 
     #[test]
     fn test_can_jump() {
-        assert!(!can_jump('u', 'z'));
+        assert!(!can_jump('u', 'w'));
+        assert!(can_jump('u', 'v'));
+        assert!(can_jump('z', 'a'));
+        assert!(can_jump('S', 'b'));
+        assert!(!can_jump('x', 'E'));
     }
 
-    // #[test]
-    // fn test_with_real_data() {
-        // let input = util::read_real_data("day12");
-        // let input = input.iter().map(|line| line.chars().collect()).collect();
-        // assert_eq!(solve(input), 123);
-    // }
+    #[test]
+    fn test_part2() {
+        let input = ["Sabqponm", "abcryxxl", "accszExk", "acctuvwj", "abdefghi"];
+        assert_eq!(part2(&input).unwrap(), 29);
+    }
+
+    #[test]
+    fn test_draw() {
+        let input = ["Sabqponm", "abcryxxl", "accszExk", "acctuvwj", "abdefghi"];
+        let map = HeightMap::new(&input).unwrap();
+        let route = map.route().unwrap();
+        assert_eq!(route.first(), Some(&Pos::new(0, 0)));
+        let expected = [">>vv<<<<", "..vvv<<^", "..vv>E^^", "..v>>>^^", "..>>>>>^"];
+        assert_eq!(map.draw(&route).to_string(), expected.join("\n"));
+    }
+
+    #[test]
+    fn test_with_real_data() {
+        let input = util::read_real_data("day12");
+        let input: Vec<&str> = input.iter().map(|line| line.as_str()).collect();
+        assert_eq!(solve(&input).unwrap(), 352);
+    }
+
+    #[test]
+    fn test_part2_with_real_data() {
+        let input = util::read_real_data("day12");
+        let input: Vec<&str> = input.iter().map(|line| line.as_str()).collect();
+        assert_eq!(part2(&input).unwrap(), 345);
+    }
 }
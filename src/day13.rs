@@ -1,10 +1,12 @@
-use std::{
-    cmp::{min, Ordering},
-    collections::VecDeque,
-};
+use std::cmp::{min, Ordering};
+
+use anyhow::Result;
+
+use crate::error::{ParseError, Scanner};
+use crate::solution::Solution;
 
 #[derive(Debug)]
-enum Packet {
+pub enum Packet {
     Number(u32),
     List(Vec<Packet>),
 }
@@ -47,144 +49,167 @@ impl PartialEq for Packet {
 
 impl PartialOrd for Packet {
     fn partial_cmp(&self, rhs: &Packet) -> Option<Ordering> {
+        Some(self.cmp(rhs))
+    }
+}
+
+impl Eq for Packet {}
+
+impl Ord for Packet {
+    fn cmp(&self, rhs: &Packet) -> Ordering {
         match (self, rhs) {
-            (Packet::Number(x), Packet::Number(y)) => Some(x.cmp(y)),
-            (x @ Packet::Number(_), y @ Packet::List(_)) => {
-                Packet::List(vec![x.clone()]).partial_cmp(y)
-            }
-            (y @ Packet::List(_), x @ Packet::Number(_)) => {
-                y.partial_cmp(&Packet::List(vec![x.clone()]))
-            }
+            (Packet::Number(x), Packet::Number(y)) => x.cmp(y),
+            (x @ Packet::Number(_), y @ Packet::List(_)) => Packet::List(vec![x.clone()]).cmp(y),
+            (y @ Packet::List(_), x @ Packet::Number(_)) => y.cmp(&Packet::List(vec![x.clone()])),
             (Packet::List(x), Packet::List(y)) => {
                 let min_length = min(x.len(), y.len());
                 for i in 0..min_length {
-                    let comparison = x[i].partial_cmp(&y[i]);
-                    if comparison != Some(Ordering::Equal) {
+                    let comparison = x[i].cmp(&y[i]);
+                    if comparison != Ordering::Equal {
                         return comparison;
                     }
                 }
-                Some(x.len().cmp(&y.len()))
+                x.len().cmp(&y.len())
             }
         }
     }
 }
 
-impl Eq for Packet {}
-
-impl Ord for Packet {
-    fn cmp(&self, rhs: &Packet) -> Ordering {
-        self.partial_cmp(rhs).unwrap()
-    }
-}
-
 impl Packet {
-    fn from_str(input: &str) -> Self {
-        let input: Vec<char> = input.chars().collect();
-        let mut input: VecDeque<char> = input.iter().copied().collect();
-        Packet::pop_packet(&mut input)
-    }
-
-    fn from_deque(input: &mut VecDeque<char>) -> Vec<Packet> {
-        let mut result = Vec::new();
-        while !input.is_empty() {
-            result.push(Self::pop_packet(input));
-        }
-        result
+    fn parse(i: usize, line: &str) -> Result<Self, ParseError> {
+        let mut scanner = Scanner::new(Day13::DAY, i, line);
+        let result = Packet::pop_packet(&mut scanner)?;
+        scanner.end()?;
+        Ok(result)
     }
 
-    fn pop_packet(input: &mut VecDeque<char>) -> Packet {
-        let token = input.pop_front().unwrap();
-        if token == '[' {
-            Packet::pop_list(input)
-        } else if token.is_numeric() {
-            input.push_front(token);
-            let n = Packet::pop_number(input);
-            Packet::Number(n)
+    fn pop_packet(scanner: &mut Scanner) -> Result<Packet, ParseError> {
+        if scanner.rest().starts_with('[') {
+            scanner.tag("[")?;
+            Packet::pop_list(scanner)
         } else {
-            panic!("unexpected token '{}'", token);
+            let n = scanner
+                .number()
+                .map_err(|_| scanner.error("a number or '['"))?;
+            Ok(Packet::Number(n))
         }
     }
 
-    fn pop_list(input: &mut VecDeque<char>) -> Packet {
-        let mut i = 1;
-        let mut temp = VecDeque::new();
-        while !input.is_empty() {
-            let c = input.pop_front().unwrap();
-            if c == '[' {
-                i += 1;
-                temp.push_back(c);
-            } else if c == ']' {
-                i -= 1;
-                if i == 0 {
-                    break;
-                }
-                temp.push_back(c);
-            } else {
-                temp.push_back(c);
-            }
+    fn pop_list(scanner: &mut Scanner) -> Result<Packet, ParseError> {
+        let mut result = Vec::new();
+        if scanner.tag("]").is_ok() {
+            return Ok(Packet::List(result));
         }
-
-        input.pop_front();
-        Packet::List(Packet::from_deque(&mut temp))
-    }
-
-    fn pop_number(input: &mut VecDeque<char>) -> u32 {
-        let mut number = String::from("");
-        while !input.is_empty() {
-            let c = input.pop_front().unwrap();
-            if c.is_numeric() {
-                number.push(c);
-            } else {
-                break;
+        loop {
+            result.push(Packet::pop_packet(scanner)?);
+            if scanner.tag("]").is_ok() {
+                return Ok(Packet::List(result));
             }
+            scanner.tag(",").map_err(|_| scanner.error("',' or ']'"))?;
         }
-        number.parse().unwrap()
     }
 }
 
-pub fn solve(input: &Vec<&str>) -> usize {
-    let mut result = 0;
+fn parse_line(input: &[&str], i: usize) -> Result<Packet, ParseError> {
+    match input.get(i) {
+        Some(line) => Packet::parse(i, line),
+        None => Err(ParseError::new(
+            Day13::DAY,
+            i + 1,
+            1,
+            "a packet",
+            "end of input",
+        )),
+    }
+}
 
+// every pair is followed by an empty line, except maybe the last
+fn parse_pairs(input: &[&str]) -> Result<Vec<(Packet, Packet)>, ParseError> {
+    let mut result = Vec::new();
     let block_count = (input.len() + 1) / 3;
     for i in 0..block_count {
-        let left = Packet::from_str(input[i * 3]);
-        let right = Packet::from_str(input[i * 3 + 1]);
-        if left < right {
-            result += i + 1;
+        let left = parse_line(input, i * 3)?;
+        let right = parse_line(input, i * 3 + 1)?;
+        if let Some(line) = input.get(i * 3 + 2) {
+            Scanner::new(Day13::DAY, i * 3 + 2, line).end()?;
         }
+        result.push((left, right));
     }
-    result
+    Ok(result)
+}
+
+pub fn solve(input: &[&str]) -> Result<usize> {
+    Day13::part1(&parse_pairs(input)?)
+}
+
+fn ordered_pairs(pairs: &[(Packet, Packet)]) -> usize {
+    let ordered = pairs
+        .iter()
+        .enumerate()
+        .filter(|(_, (left, right))| left < right);
+    ordered.map(|(i, _)| i + 1).sum()
 }
 
-pub fn part2(input: &[&str]) -> usize {
-    let mut packets: Vec<Packet> = input
+pub fn part2(input: &[&str]) -> Result<usize> {
+    Day13::part2(&parse_pairs(input)?)
+}
+
+fn decoder_key(pairs: &[(Packet, Packet)]) -> usize {
+    let mut packets: Vec<&Packet> = pairs
         .iter()
-        .filter(|line| !line.is_empty())
-        .map(|line| Packet::from_str(line))
+        .flat_map(|(left, right)| [left, right])
         .collect();
 
     let two = Packet::List(vec![Packet::List(vec![Packet::Number(2)])]);
     let six = Packet::List(vec![Packet::List(vec![Packet::Number(6)])]);
 
-    packets.push(six.clone());
-    packets.push(two.clone());
+    packets.push(&six);
+    packets.push(&two);
 
     packets.sort();
 
-    let six_pos = 1 + packets.iter().position(|x| *x == six).unwrap();
-    let two_pos = 1 + packets.iter().position(|x| *x == two).unwrap();
+    let six_pos = 1 + packets.iter().position(|x| **x == six).unwrap();
+    let two_pos = 1 + packets.iter().position(|x| **x == two).unwrap();
     six_pos * two_pos
 }
 
+pub struct Day13;
+
+impl Solution for Day13 {
+    const YEAR: u32 = 2022;
+    const DAY: u32 = 13;
+
+    type Input = Vec<(Packet, Packet)>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let input: Vec<&str> = input.lines().collect();
+        Ok(parse_pairs(&input)?)
+    }
+
+    fn part1(pairs: &Self::Input) -> Result<usize> {
+        Ok(ordered_pairs(pairs))
+    }
+
+    fn part2(pairs: &Self::Input) -> Result<usize> {
+        Ok(decoder_key(pairs))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::util;
 
+    fn packet(input: &str) -> Packet {
+        Packet::parse(0, input).unwrap()
+    }
+
     #[test]
     fn test_parse_simple_list() {
         assert_eq!(
-            Packet::from_str("[1,1,3,1,1]"),
+            packet("[1,1,3,1,1]"),
             Packet::List(vec![
                 Packet::Number(1),
                 Packet::Number(1),
@@ -198,7 +223,7 @@ mod tests {
     #[test]
     fn test_parse_list_of_lists() {
         assert_eq!(
-            Packet::from_str("[[1],[2,3,4]]"),
+            packet("[[1],[2,3,4]]"),
             Packet::List(vec![
                 Packet::List(vec![Packet::Number(1)]),
                 Packet::List(vec![
@@ -213,7 +238,7 @@ mod tests {
     #[test]
     fn test_parse_composite() {
         assert_eq!(
-            Packet::from_str("[[1],4]"),
+            packet("[[1],4]"),
             Packet::List(vec![
                 Packet::List(vec![Packet::Number(1)]),
                 Packet::Number(4)
@@ -223,13 +248,13 @@ mod tests {
 
     #[test]
     fn test_parse_empty() {
-        assert_eq!(Packet::from_str("[]"), Packet::List(vec![]));
+        assert_eq!(packet("[]"), Packet::List(vec![]));
     }
 
     #[test]
     fn test_parse_composite_empty() {
         assert_eq!(
-            Packet::from_str("[[[]]]"),
+            packet("[[[]]]"),
             Packet::List(vec![Packet::List(vec![Packet::List(vec![])])])
         );
     }
@@ -237,7 +262,7 @@ mod tests {
     #[test]
     fn test_parse_hierarchy() {
         assert_eq!(
-            Packet::from_str("[1,[2,[3,[4,[5,6,7]]]],8,9]"),
+            packet("[1,[2,[3,[4,[5,6,7]]]],8,9]"),
             Packet::List(vec![
                 Packet::Number(1),
                 Packet::List(vec![
@@ -260,38 +285,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_error() {
+        let error = Packet::parse(4, "[1,[2;3]]").unwrap_err();
+        assert_eq!(error, ParseError::new(13, 5, 6, "',' or ']'", ";3]]"));
+
+        let error = Packet::parse(0, "[1,]").unwrap_err();
+        assert_eq!(error, ParseError::new(13, 1, 4, "a number or '['", "]"));
+
+        let error = Packet::parse(0, "[1]]").unwrap_err();
+        assert_eq!(error, ParseError::new(13, 1, 4, "end of line", "]"));
+
+        let error = parse_pairs(&["[1]", "[2]", "[3]", "[4]"]).unwrap_err();
+        assert_eq!(error, ParseError::new(13, 3, 1, "end of line", "[3]"));
+    }
+
     #[test]
     fn test_cmp() {
-        let left = Packet::from_str("[1,1,3,1,1]");
-        let right = Packet::from_str("[1,1,5,1,1]");
+        let left = packet("[1,1,3,1,1]");
+        let right = packet("[1,1,5,1,1]");
         assert!(left < right);
 
-        let left = Packet::from_str("[[1],[2,3,4]]");
-        let right = Packet::from_str("[[1],4]");
+        let left = packet("[[1],[2,3,4]]");
+        let right = packet("[[1],4]");
         assert!(left < right);
 
-        let left = Packet::from_str("[9]");
-        let right = Packet::from_str("[[8,7,6]]");
+        let left = packet("[9]");
+        let right = packet("[[8,7,6]]");
         assert!(left > right);
 
-        let left = Packet::from_str("[[4,4],4,4]");
-        let right = Packet::from_str("[[4,4],4,4,4]");
+        let left = packet("[[4,4],4,4]");
+        let right = packet("[[4,4],4,4,4]");
         assert!(left < right);
 
-        let left = Packet::from_str("[7,7,7,7]");
-        let right = Packet::from_str("[7,7,7]");
+        let left = packet("[7,7,7,7]");
+        let right = packet("[7,7,7]");
         assert!(left > right);
 
-        let left = Packet::from_str("[]");
-        let right = Packet::from_str("[3]");
+        let left = packet("[]");
+        let right = packet("[3]");
         assert!(left < right);
 
-        let left = Packet::from_str("[[[]]]");
-        let right = Packet::from_str("[[]]");
+        let left = packet("[[[]]]");
+        let right = packet("[[]]");
         assert!(left > right);
 
-        let left = Packet::from_str("[1,[2,[3,[4,[5,6,7]]]],8,9]");
-        let right = Packet::from_str("[1,[2,[3,[4,[5,6,0]]]],8,9]");
+        let left = packet("[1,[2,[3,[4,[5,6,7]]]],8,9]");
+        let right = packet("[1,[2,[3,[4,[5,6,0]]]],8,9]");
         assert!(left > right);
     }
 
@@ -323,14 +363,14 @@ mod tests {
             "[1,[2,[3,[4,[5,6,0]]]],8,9]",
         ];
 
-        assert_eq!(solve(&input), 13);
+        assert_eq!(solve(&input).unwrap(), 13);
     }
 
     #[test]
     fn test_with_real_data() {
         let input = util::read_real_data("day13");
-        let input = input.iter().map(|line| line.as_str()).collect();
-        assert_eq!(solve(&input), 5808);
+        let input: Vec<&str> = input.iter().map(|line| line.as_str()).collect();
+        assert_eq!(solve(&input).unwrap(), 5808);
     }
 
     #[test]
@@ -361,13 +401,13 @@ mod tests {
             "[1,[2,[3,[4,[5,6,0]]]],8,9]",
         ];
 
-        assert_eq!(part2(&input), 140);
+        assert_eq!(part2(&input).unwrap(), 140);
     }
 
     #[test]
     fn test_part2_with_real_data() {
         let input = util::read_real_data("day13");
         let input: Vec<&str> = input.iter().map(|line| line.as_str()).collect();
-        assert_eq!(part2(&input), 22713);
+        assert_eq!(part2(&input).unwrap(), 22713);
     }
 }
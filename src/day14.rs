@@ -1,56 +1,34 @@
-use std::{cmp::Ordering, collections::HashSet};
+use std::cmp::Ordering;
 
-#[derive(PartialEq, Eq, Copy, Clone, Debug, Hash)]
-struct Point {
-    col: usize,
-    row: usize,
-}
-
-const START_POINT: Point = Point { col: 500, row: 0 };
+use anyhow::Result;
 
-impl Point {
-    fn parse(input: &str) -> Self {
-        let mut tokens = input.split(',');
-        let col = tokens.next().unwrap().parse().unwrap();
-        let row = tokens.next().unwrap().parse().unwrap();
-        Self { col, row }
-    }
-
-    fn bottom(&self) -> Self {
-        Point {
-            col: self.col,
-            row: self.row + 1,
-        }
-    }
+use crate::error::{ParseError, Scanner};
+use crate::grid::{Pos, SparseGrid};
+use crate::solution::Solution;
 
-    fn bottom_left(&self) -> Self {
-        Point {
-            col: self.col - 1,
-            row: self.row + 1,
-        }
-    }
+const START_POINT: Pos = Pos::new(500, 0);
 
-    fn bottom_right(&self) -> Self {
-        Point {
-            col: self.col + 1,
-            row: self.row + 1,
-        }
-    }
+fn parse_point(scanner: &mut Scanner) -> Result<Pos, ParseError> {
+    let col = scanner.number()?;
+    scanner.tag(",")?;
+    let row = scanner.number()?;
+    Ok(Pos::new(col, row))
 }
 
-impl From<(usize, usize)> for Point {
-    fn from((col, row): (usize, usize)) -> Self {
-        Self { col, row }
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tile {
+    Rock,
+    Sand,
 }
 
 #[derive(Debug)]
-struct Grid {
-    sand: Point,
+struct Cave {
+    sand: Pos,
     state: SandState,
     counter: u32,
-    occupied: HashSet<Point>,
-    floor: Option<usize>,
+    occupied: SparseGrid<Tile>,
+    lowest_rock: i32,
+    floor: Option<i32>,
 }
 
 // state for NEXT move
@@ -64,33 +42,38 @@ enum SandState {
     Filled,
 }
 
-pub fn solve(input: &Vec<&str>, with_floor: bool) -> u32 {
+fn parse_lines(input: &[&str]) -> Result<Vec<Line>, ParseError> {
     let mut lines = Vec::new();
-    for line in input {
-        let current = Line::parse(line);
+    for (i, line) in input.iter().enumerate() {
+        let current = Line::parse(i, line)?;
         lines.extend(current);
     }
-    let mut grid = Grid::from(lines, with_floor);
-    grid.run()
+    Ok(lines)
+}
+
+pub fn solve(input: &Vec<&str>, with_floor: bool) -> Result<u32> {
+    let lines = parse_lines(input)?;
+    Ok(Cave::from(&lines, with_floor).run())
 }
 
-impl Grid {
-    fn from(lines: Vec<Line>, with_floor: bool) -> Self {
-        let mut occupied = HashSet::new();
+impl Cave {
+    fn from(lines: &[Line], with_floor: bool) -> Self {
+        let mut occupied = SparseGrid::new();
         for line in lines.iter() {
-            occupied.extend(line.points());
+            for point in line.points() {
+                occupied.insert(point, Tile::Rock);
+            }
         }
 
-        let mut floor = None;
-        if with_floor {
-            let floor_row = occupied.iter().map(|point| point.row).max().unwrap() + 2;
-            floor = Some(floor_row);
-        }
+        let lowest_rock = occupied.positions().map(|point| point.row).max();
+        let lowest_rock = lowest_rock.unwrap_or(0);
+        let floor = with_floor.then_some(lowest_rock + 2);
 
         Self {
             counter: 0,
             sand: START_POINT,
             occupied,
+            lowest_rock,
             state: SandState::Down,
             floor,
         }
@@ -101,7 +84,7 @@ impl Grid {
         self.counter
     }
 
-    fn is_occupied(&self, point: &Point) -> bool {
+    fn is_occupied(&self, point: Pos) -> bool {
         let mut result = self.occupied.contains(point);
         if let Some(row) = self.floor {
             result |= point.row == row;
@@ -109,11 +92,9 @@ impl Grid {
         result
     }
 
-    fn is_over_abyss(&self, point: Point) -> bool {
-        if self.floor.is_some() {
-            return false;
-        }
-        self.occupied.iter().all(|p| p.row < point.row)
+    // sand never settles below the lowest rock
+    fn is_over_abyss(&self, point: Pos) -> bool {
+        self.floor.is_none() && point.row > self.lowest_rock
     }
 
     fn one_step(&mut self) -> bool {
@@ -127,12 +108,12 @@ impl Grid {
     }
 
     fn on_down(&mut self) -> bool {
-        let bottom = self.sand.bottom();
+        let bottom = self.sand + Pos::DOWN;
         if self.is_over_abyss(self.sand) {
             self.state = SandState::Abyss;
             return true;
         }
-        if !self.is_occupied(&bottom) {
+        if !self.is_occupied(bottom) {
             self.sand = bottom;
         } else {
             self.state = SandState::Left;
@@ -141,8 +122,8 @@ impl Grid {
     }
 
     fn on_left(&mut self) -> bool {
-        let bottom_left = self.sand.bottom_left();
-        if !self.is_occupied(&bottom_left) {
+        let bottom_left = self.sand + Pos::DOWN + Pos::LEFT;
+        if !self.is_occupied(bottom_left) {
             self.sand = bottom_left;
             self.state = SandState::Down;
         } else {
@@ -152,8 +133,8 @@ impl Grid {
     }
 
     fn on_right(&mut self) -> bool {
-        let bottom_right = self.sand.bottom_right();
-        if !self.is_occupied(&bottom_right) {
+        let bottom_right = self.sand + Pos::DOWN + Pos::RIGHT;
+        if !self.is_occupied(bottom_right) {
             self.sand = bottom_right;
             self.state = SandState::Down;
         } else {
@@ -168,7 +149,7 @@ impl Grid {
             self.counter += 1;
             return true;
         }
-        self.occupied.insert(self.sand);
+        self.occupied.insert(self.sand, Tile::Sand);
         self.sand = START_POINT;
         self.state = SandState::Down;
         self.counter += 1;
@@ -177,19 +158,25 @@ impl Grid {
 }
 
 #[derive(PartialEq, Eq, Debug)]
-struct Line {
-    start: Point,
-    end: Point,
+pub struct Line {
+    start: Pos,
+    end: Pos,
 }
 
 impl Line {
-    fn parse(input: &str) -> Vec<Self> {
-        let tokens: Vec<&str> = input.split(" -> ").collect();
-        let len = tokens.len() - 1;
+    fn parse(i: usize, input: &str) -> Result<Vec<Self>, ParseError> {
+        let mut scanner = Scanner::new(Day14::DAY, i, input);
+        let mut previous = parse_point(&mut scanner)?;
         let mut result = Vec::new();
-        for i in 0..len {
-            let mut start = Point::parse(tokens[i]);
-            let mut end = Point::parse(tokens[i + 1]);
+        while !scanner.is_empty() {
+            scanner.tag(" -> ")?;
+            let at_end = scanner.clone();
+            let mut start = previous;
+            let mut end = parse_point(&mut scanner)?;
+            previous = end;
+            if start.col != end.col && start.row != end.row {
+                return Err(at_end.error("a point in line with the previous one"));
+            }
             match start.col.cmp(&end.col) {
                 Ordering::Equal if end.row < start.row => {
                     (start, end) = (end, start);
@@ -202,32 +189,48 @@ impl Line {
             let line = Self { start, end };
             result.push(line);
         }
-        result
+        Ok(result)
     }
 
-    fn points(&self) -> Vec<Point> {
+    fn points(&self) -> Vec<Pos> {
         let mut result = Vec::new();
         if self.start.col == self.end.col {
-            let end = self.end.row + 1;
-            for i in self.start.row..end {
-                result.push(Point {
-                    col: self.start.col,
-                    row: i,
-                });
+            for row in self.start.row..=self.end.row {
+                result.push(Pos::new(self.start.col, row));
             }
         } else {
-            let end = self.end.col + 1;
-            for i in self.start.col..end {
-                result.push(Point {
-                    col: i,
-                    row: self.start.row,
-                });
+            for col in self.start.col..=self.end.col {
+                result.push(Pos::new(col, self.start.row));
             }
         }
         result
     }
 }
 
+pub struct Day14;
+
+impl Solution for Day14 {
+    const YEAR: u32 = 2022;
+    const DAY: u32 = 14;
+
+    type Input = Vec<Line>;
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let input: Vec<&str> = input.lines().collect();
+        Ok(parse_lines(&input)?)
+    }
+
+    fn part1(lines: &Self::Input) -> Result<u32> {
+        Ok(Cave::from(lines, false).run())
+    }
+
+    fn part2(lines: &Self::Input) -> Result<u32> {
+        Ok(Cave::from(lines, true).run())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -235,24 +238,36 @@ mod tests {
 
     #[test]
     fn test_parse_line() {
-        let actual = Line::parse("503,4 -> 502,4 -> 502,9 -> 494,9");
+        let actual = Line::parse(0, "503,4 -> 502,4 -> 502,9 -> 494,9").unwrap();
         let expected = vec![
             Line {
-                start: (502, 4).into(),
-                end: (503, 4).into(),
+                start: Pos::new(502, 4),
+                end: Pos::new(503, 4),
             },
             Line {
-                start: (502, 4).into(),
-                end: (502, 9).into(),
+                start: Pos::new(502, 4),
+                end: Pos::new(502, 9),
             },
             Line {
-                start: (494, 9).into(),
-                end: (502, 9).into(),
+                start: Pos::new(494, 9),
+                end: Pos::new(502, 9),
             },
         ];
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_parse_line_error() {
+        let error = Line::parse(1, "503,4 -> 502,5").unwrap_err();
+        assert_eq!(
+            error,
+            ParseError::new(14, 2, 10, "a point in line with the previous one", "502,5")
+        );
+
+        let error = Line::parse(1, "503,4 => 502,4").unwrap_err();
+        assert_eq!(error, ParseError::new(14, 2, 6, "\" -> \"", "=>"));
+    }
+
     #[test]
     fn test_solve() {
         let input = vec![
@@ -260,21 +275,51 @@ mod tests {
             "503,4 -> 502,4 -> 502,9 -> 494,9",
         ];
 
-        assert_eq!(solve(&input, false), 24);
+        assert_eq!(solve(&input, false).unwrap(), 24);
+    }
+
+    #[test]
+    fn test_render() {
+        let input = [
+            "498,4 -> 498,6 -> 496,6",
+            "503,4 -> 502,4 -> 502,9 -> 494,9",
+        ];
+        let lines: Vec<Line> = input
+            .iter()
+            .enumerate()
+            .flat_map(|(i, line)| Line::parse(i, line).unwrap())
+            .collect();
+        let mut cave = Cave::from(&lines, false);
+        cave.run();
+        let expected = [
+            "......o...",
+            ".....ooo..",
+            "....#ooo##",
+            "...o#ooo#.",
+            "..###ooo#.",
+            "....oooo#.",
+            ".o.ooooo#.",
+            "#########.",
+        ];
+        let actual = cave.occupied.render('.', |tile| match tile {
+            Tile::Rock => '#',
+            Tile::Sand => 'o',
+        });
+        assert_eq!(actual, expected.join("\n"));
     }
 
     #[test]
     fn test_with_real_data() {
         let input = util::read_real_data("day14");
         let input = input.iter().map(|line| line.as_str()).collect();
-        assert_eq!(solve(&input, false), 768);
+        assert_eq!(solve(&input, false).unwrap(), 768);
     }
 
     #[test]
     fn test_part2_with_real_data() {
         let input = util::read_real_data("day14");
         let input = input.iter().map(|line| line.as_str()).collect();
-        assert_eq!(solve(&input, true), 26686);
+        assert_eq!(solve(&input, true).unwrap(), 26686);
     }
 
     #[test]
@@ -284,6 +329,6 @@ mod tests {
             "503,4 -> 502,4 -> 502,9 -> 494,9",
         ];
 
-        assert_eq!(solve(&input, true), 93);
+        assert_eq!(solve(&input, true).unwrap(), 93);
     }
 }
@@ -1,154 +1,188 @@
-use std::sync::mpsc::Sender;
-use std::sync::{mpsc, Arc};
-use std::{collections::HashSet, thread};
+use anyhow::{bail, Result};
 
-type Point = (i32, i32);
+use crate::error::{ParseError, Scanner};
+use crate::grid::Pos;
+use crate::solution::Solution;
 
-struct Area {
-    sensor: Point,
-    radius: usize,
+const TUNING_MULTIPLIER: i64 = 4000000;
+
+struct Sensor {
+    pos: Pos,
+    radius: i32,
 }
 
-impl Area {
-    fn from(sensor: Point, beacon: Point) -> Self {
-        let radius = manhattan_distance(&sensor, &beacon);
-        Self { sensor, radius }
+impl Sensor {
+    fn new(pos: Pos, beacon: Pos) -> Self {
+        let radius = pos.manhattan(beacon) as i32;
+        Self { pos, radius }
     }
 
-    fn contains(&self, point: &Point) -> bool {
-        manhattan_distance(&self.sensor, point) <= self.radius
+    fn covers(&self, pos: Pos) -> bool {
+        self.pos.manhattan(pos) as i32 <= self.radius
     }
-}
 
-pub fn part2(input: Vec<&str>, border: usize) -> usize {
-    let input = parse(input);
-    solve_part2(input, border)
+    // inclusive range of columns covered on `row`
+    fn coverage(&self, row: i32) -> Option<(i32, i32)> {
+        let reach = self.radius - (self.pos.row - row).abs();
+        (reach >= 0).then_some((self.pos.col - reach, self.pos.col + reach))
+    }
 }
 
-fn solve_part2(input: Vec<(Point, Point)>, border: usize) -> usize {
-    let areas: Vec<Area> = input
+// columns covered on `row` as sorted, disjoint, inclusive ranges
+fn coverage(sensors: &[Sensor], row: i32) -> Vec<(i32, i32)> {
+    let mut ranges: Vec<(i32, i32)> = sensors
         .iter()
-        .map(move |(sensor, beacon)| Area::from(*sensor, *beacon))
+        .filter_map(|sensor| sensor.coverage(row))
         .collect();
-    let areas = Arc::new(areas);
-    let region_size = border / 4 - 1;
-    let (sender, receiver) = mpsc::channel();
-    for x in 0..region_size {
-        for y in 0..region_size {
-            let areas = Arc::clone(&areas);
-            let sender = sender.clone();
-            thread::spawn(move || search_in_region(region_size, areas, x, y, sender));
+    ranges.sort_unstable();
+    let mut merged: Vec<(i32, i32)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some((_, last_end)) if start <= *last_end + 1 => *last_end = end.max(*last_end),
+            _ => merged.push((start, end)),
         }
     }
-    for _ in 0..16 {
-        if let Some(result) = receiver.recv().unwrap() {
-            return result;
-        }
-    }
-    unreachable!("fail");
+    merged
 }
 
-fn search_in_region(
-    region_size: usize,
-    areas: Arc<Vec<Area>>,
-    x: usize,
-    y: usize,
-    sender: Sender<Option<usize>>,
-) {
-    for col in 0..region_size {
-        for row in 0..region_size {
-            let global_col = col + x * region_size;
-            let global_row = row + y * region_size;
-            if !is_covered(&areas, &(global_col as i32, global_row as i32)) {
-                sender
-                    .send(Some(global_col * 4000000 + global_row))
-                    .unwrap();
-                return;
-            }
-        }
-    }
-    if sender.send(None).is_err() {
-        // whatever
-    }
-}
+fn solve(input: &[(Pos, Pos)], row: usize) -> usize {
+    let row = row as i32;
+    let sensors: Vec<Sensor> = input
+        .iter()
+        .map(|(sensor, beacon)| Sensor::new(*sensor, *beacon))
+        .collect();
+    let ranges = coverage(&sensors, row);
+    let covered: i32 = ranges.iter().map(|(start, end)| end - start + 1).sum();
 
-fn is_covered(areas: &[Area], point: &Point) -> bool {
-    for area in areas.iter() {
-        if area.contains(point) {
-            return true;
-        }
-    }
-    false
+    // sensors and beacons themselves aren't counted
+    let mut taken: Vec<i32> = input
+        .iter()
+        .flat_map(|(sensor, beacon)| [*sensor, *beacon])
+        .filter(|pos| pos.row == row)
+        .map(|pos| pos.col)
+        .collect();
+    taken.sort_unstable();
+    taken.dedup();
+    let taken = taken
+        .iter()
+        .filter(|col| {
+            ranges
+                .iter()
+                .any(|(start, end)| (start..=end).contains(col))
+        })
+        .count();
+    covered as usize - taken
 }
 
-fn manhattan_distance((x1, y1): &Point, (x2, y2): &Point) -> usize {
-    ((x1 - x2).abs() + (y1 - y2).abs()) as usize
+pub fn part2(input: Vec<&str>, border: usize) -> Result<usize> {
+    solve_part2(&parse(input)?, border as i32)
 }
 
-fn covered_area_on_row((col, row): &Point, radius: usize, target_row: usize) -> Vec<Point> {
-    let radius_signed = radius as i32;
-    let mut smallest = 0;
-    if radius_signed <= *row {
-        smallest = row - radius_signed;
-    }
-    let biggest = *row as usize + radius;
-    if !(target_row <= biggest && target_row >= smallest as usize) {
-        return vec![];
+// the only uncovered position sits just outside the edges of several sensors, so it's
+// where a line running along one sensor's edge crosses another's, where such a line
+// meets the border of the search area, or in a corner
+fn solve_part2(input: &[(Pos, Pos)], border: i32) -> Result<usize> {
+    let sensors: Vec<Sensor> = input
+        .iter()
+        .map(|(sensor, beacon)| Sensor::new(*sensor, *beacon))
+        .collect();
+    // lines col + row = a and col - row = b just outside each sensor
+    let mut rising = Vec::new();
+    let mut falling = Vec::new();
+    for sensor in sensors.iter() {
+        let (col, row, reach) = (sensor.pos.col, sensor.pos.row, sensor.radius + 1);
+        rising.extend([col + row - reach, col + row + reach]);
+        falling.extend([col - row - reach, col - row + reach]);
     }
-
-    let mut result = Vec::new();
-    let radius_signed = radius_signed - (row - target_row as i32).abs();
-    for i in -radius_signed..=radius_signed {
-        let candidate = (i + col, target_row as i32);
-        result.push(candidate);
+    let crossings = rising.iter().flat_map(|a| {
+        falling
+            .iter()
+            .filter(move |b| (a + *b) % 2 == 0)
+            .map(move |b| Pos::new((a + b) / 2, (a - b) / 2))
+    });
+    let edges = rising
+        .iter()
+        .flat_map(|&a| {
+            [
+                Pos::new(0, a),
+                Pos::new(a, 0),
+                Pos::new(border, a - border),
+                Pos::new(a - border, border),
+            ]
+        })
+        .chain(falling.iter().flat_map(|&b| {
+            [
+                Pos::new(0, -b),
+                Pos::new(b, 0),
+                Pos::new(border, border - b),
+                Pos::new(b + border, border),
+            ]
+        }));
+    let corners = [
+        Pos::new(0, 0),
+        Pos::new(border, 0),
+        Pos::new(0, border),
+        Pos::new(border, border),
+    ];
+    let in_bounds = |pos: &Pos| (0..=border).contains(&pos.col) && (0..=border).contains(&pos.row);
+    let found = crossings
+        .chain(edges)
+        .chain(corners)
+        .filter(in_bounds)
+        .find(|pos| !sensors.iter().any(|sensor| sensor.covers(*pos)));
+    match found {
+        Some(pos) => Ok((pos.col as i64 * TUNING_MULTIPLIER + pos.row as i64) as usize),
+        None => bail!("day 15: every position is covered by a sensor"),
     }
-    result
 }
 
-fn solve(input: Vec<(Point, Point)>, row: usize) -> usize {
-    let mut taken = HashSet::new();
-    for (sensor, beacon) in input.iter() {
-        taken.insert(sensor);
-        taken.insert(beacon);
-    }
+fn parse(input: Vec<&str>) -> Result<Vec<(Pos, Pos)>, ParseError> {
     let mut result = Vec::new();
-    for (sensor, beacon) in input.iter() {
-        let radius = manhattan_distance(sensor, beacon);
-        let area = covered_area_on_row(sensor, radius, row);
-        for point in area {
-            if !taken.contains(&point) {
-                result.push(point);
-            }
-        }
+    for (i, line) in input.into_iter().enumerate() {
+        let mut scanner = Scanner::new(Day15::DAY, i, line);
+        scanner.tag("Sensor at ")?;
+        let sensor = parse_point(&mut scanner)?;
+        scanner.tag(": closest beacon is at ")?;
+        let beacon = parse_point(&mut scanner)?;
+        scanner.end()?;
+        result.push((sensor, beacon));
     }
-    let result: HashSet<_> = result.iter().collect();
-    result.len()
+    Ok(result)
 }
 
-fn parse(input: Vec<&str>) -> Vec<(Point, Point)> {
-    let mut result = Vec::new();
-    for line in input {
-        let mut tokens = line.split(' ');
-        let sensor = (
-            parse_value(tokens.nth(2).unwrap()),
-            parse_value(tokens.next().unwrap()),
-        );
-        let beacon = (
-            parse_value(tokens.nth(4).unwrap()),
-            parse_value(tokens.next().unwrap()),
-        );
-        result.push((sensor, beacon));
-    }
-    result
+fn parse_point(scanner: &mut Scanner) -> Result<Pos, ParseError> {
+    scanner.tag("x=")?;
+    let x = scanner.number()?;
+    scanner.tag(", y=")?;
+    let y = scanner.number()?;
+    Ok(Pos::new(x, y))
 }
 
-fn parse_value(input: &str) -> i32 {
-    input[2..].replace([',', ':'], "").parse().unwrap()
+pub fn part1(input: Vec<&str>, row: usize) -> Result<usize> {
+    Ok(solve(&parse(input)?, row))
 }
 
-pub fn part1(input: Vec<&str>, row: usize) -> usize {
-    let input = parse(input);
-    solve(input, row)
+pub struct Day15;
+
+impl Solution for Day15 {
+    const YEAR: u32 = 2022;
+    const DAY: u32 = 15;
+
+    type Input = Vec<(Pos, Pos)>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(parse(input.lines().collect())?)
+    }
+
+    fn part1(input: &Self::Input) -> Result<usize> {
+        Ok(solve(input, 2000000))
+    }
+
+    fn part2(input: &Self::Input) -> Result<usize> {
+        solve_part2(input, 4000000)
+    }
 }
 
 #[cfg(test)]
@@ -160,15 +194,15 @@ mod tests {
     fn test_part1_with_real_data() {
         let input = util::read_real_data("day15");
         let input = input.iter().map(|line| line.as_str()).collect();
-        assert_eq!(part1(input, 2000000), 4919281);
+        assert_eq!(part1(input, 2000000).unwrap(), 4919281);
     }
 
-    // #[test]
-    // fn test_part2_with_real_data() {
-    //     let input = util::read_real_data("day15");
-    //     let input = input.iter().map(|line| line.as_str()).collect();
-    //     assert_eq!(part2(input, 4000000), 123);
-    // }
+    #[test]
+    fn test_part2_with_real_data() {
+        let input = util::read_real_data("day15");
+        let input = input.iter().map(|line| line.as_str()).collect();
+        assert_eq!(part2(input, 4000000).unwrap(), 12630143363767);
+    }
 
     #[test]
     fn test_part1() {
@@ -189,7 +223,7 @@ mod tests {
             "Sensor at x=20, y=1: closest beacon is at x=15, y=3",
         ];
 
-        assert_eq!(part1(input, 10), 26);
+        assert_eq!(part1(input, 10).unwrap(), 26);
     }
 
     #[test]
@@ -211,6 +245,29 @@ mod tests {
             "Sensor at x=20, y=1: closest beacon is at x=15, y=3",
         ];
 
-        assert_eq!(part2(input, 20), 56000011);
+        assert_eq!(part2(input, 20).unwrap(), 56000011);
+    }
+
+    #[test]
+    fn test_part2_gap_on_edge() {
+        // only x=0, y=2 is free, and no two sensor edges cross there
+        let input = vec![
+            "Sensor at x=1, y=0: closest beacon is at x=1, y=-2",
+            "Sensor at x=-2, y=4: closest beacon is at x=-5, y=4",
+            "Sensor at x=4, y=3: closest beacon is at x=7, y=3",
+        ];
+
+        assert_eq!(part2(input, 4).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let input = vec![
+            "Sensor at x=2, y=18: closest beacon is at x=-2, y=15",
+            "Sensor at x=9, y=16: closest beacon at x=10, y=16",
+        ];
+        let error = parse(input).unwrap_err();
+        let expected = "\": closest beacon is at \"";
+        assert_eq!(error, ParseError::new(15, 2, 20, expected, ":"));
     }
 }
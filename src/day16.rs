@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use rayon::prelude::*;
+
+use crate::error::{ParseError, Scanner};
+use crate::solution::Solution;
+
+const START: &str = "AA";
+const UNREACHABLE: u32 = u32::MAX / 2;
+
+// only the valves worth opening, the start is the last row of `distances`
+pub struct Valves {
+    flows: Vec<u32>,
+    distances: Vec<Vec<u32>>,
+}
+
+impl Valves {
+    fn start(&self) -> usize {
+        self.flows.len()
+    }
+
+    // best pressure released for every set of opened valves (a bitmask) within `minutes`
+    fn released(&self, minutes: u32) -> HashMap<u64, u32> {
+        (0..self.flows.len())
+            .into_par_iter()
+            .map(|first| {
+                let mut best = HashMap::from([(0, 0)]);
+                self.open(self.start(), first, minutes, 0, 0, &mut best);
+                best
+            })
+            .reduce(HashMap::new, |mut left, right| {
+                for (opened, released) in right {
+                    let best = left.entry(opened).or_insert(0);
+                    *best = (*best).max(released);
+                }
+                left
+            })
+    }
+
+    fn open(
+        &self,
+        from: usize,
+        valve: usize,
+        minutes: u32,
+        opened: u64,
+        released: u32,
+        best: &mut HashMap<u64, u32>,
+    ) {
+        let cost = self.distances[from][valve] + 1;
+        if opened & (1 << valve) != 0 || cost >= minutes {
+            return;
+        }
+        let minutes = minutes - cost;
+        let opened = opened | (1 << valve);
+        let released = released + minutes * self.flows[valve];
+        let entry = best.entry(opened).or_insert(0);
+        *entry = (*entry).max(released);
+        for next in 0..self.flows.len() {
+            self.open(valve, next, minutes, opened, released, best);
+        }
+    }
+}
+
+pub fn parse(input: &[&str]) -> Result<Valves, ParseError> {
+    let mut names = HashMap::new();
+    let mut valves = Vec::new();
+    for (i, line) in input.iter().enumerate() {
+        let mut scanner = Scanner::new(Day16::DAY, i, line);
+        scanner.tag("Valve ")?;
+        let at_name = scanner.clone();
+        let name = scanner.until(' ');
+        if names.contains_key(name) {
+            return Err(at_name.error("a valve not defined before"));
+        }
+        scanner.tag(" has flow rate=")?;
+        let flow: u32 = scanner.number()?;
+        scanner.tag("; tunnel")?;
+        if scanner.tag("s lead to valves ").is_err() {
+            scanner.tag(" leads to valve ")?;
+        }
+        let mut tunnels = Vec::new();
+        loop {
+            let at = scanner.clone();
+            tunnels.push((at, scanner.until(',')));
+            if scanner.is_empty() {
+                break;
+            }
+            scanner.tag(", ")?;
+        }
+        names.insert(name, valves.len());
+        valves.push((name, flow, tunnels));
+    }
+
+    // all-pairs shortest paths between every valve, then keep the useful ones
+    let n = valves.len();
+    let mut distances = vec![vec![UNREACHABLE; n]; n];
+    for (i, (_, _, tunnels)) in valves.iter().enumerate() {
+        distances[i][i] = 0;
+        for (at, name) in tunnels {
+            let j = names
+                .get(name)
+                .ok_or_else(|| at.error("a valve defined in the input"))?;
+            distances[i][*j] = 1;
+        }
+    }
+    for k in 0..n {
+        for i in 0..n {
+            for j in 0..n {
+                let through = distances[i][k] + distances[k][j];
+                if through < distances[i][j] {
+                    distances[i][j] = through;
+                }
+            }
+        }
+    }
+
+    let start = *names
+        .get(START)
+        .ok_or_else(|| ParseError::new(Day16::DAY, n + 1, 1, "valve AA", "end of input"))?;
+    let mut useful: Vec<usize> = (0..n).filter(|i| valves[*i].1 > 0).collect();
+    if useful.len() > u64::BITS as usize {
+        let expected = "at most 64 valves with flow";
+        return Err(ParseError::new(Day16::DAY, n, 1, expected, input[n - 1]));
+    }
+    let flows = useful.iter().map(|i| valves[*i].1).collect();
+    useful.push(start);
+    let distances = useful
+        .iter()
+        .map(|i| useful.iter().map(|j| distances[*i][*j]).collect())
+        .collect();
+    Ok(Valves { flows, distances })
+}
+
+pub fn solve(valves: &Valves) -> u32 {
+    valves.released(30).into_values().max().unwrap_or(0)
+}
+
+// you and the elephant open disjoint sets of valves
+pub fn part2(valves: &Valves) -> u32 {
+    let best: Vec<(u64, u32)> = valves.released(26).into_iter().collect();
+    best.par_iter()
+        .map(|(mine, released)| {
+            best.iter()
+                .filter(|(elephants, _)| mine & elephants == 0)
+                .map(|(_, other)| released + other)
+                .max()
+                .unwrap_or(0)
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    const YEAR: u32 = 2022;
+    const DAY: u32 = 16;
+
+    type Input = Valves;
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let input: Vec<&str> = input.lines().collect();
+        Ok(parse(&input)?)
+    }
+
+    fn part1(valves: &Valves) -> Result<u32> {
+        Ok(solve(valves))
+    }
+
+    fn part2(valves: &Valves) -> Result<u32> {
+        Ok(part2(valves))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: [&str; 10] = [
+        "Valve AA has flow rate=0; tunnels lead to valves DD, II, BB",
+        "Valve BB has flow rate=13; tunnels lead to valves CC, AA",
+        "Valve CC has flow rate=2; tunnels lead to valves DD, BB",
+        "Valve DD has flow rate=20; tunnels lead to valves CC, AA, EE",
+        "Valve EE has flow rate=3; tunnels lead to valves FF, DD",
+        "Valve FF has flow rate=0; tunnels lead to valves EE, GG",
+        "Valve GG has flow rate=0; tunnels lead to valves FF, HH",
+        "Valve HH has flow rate=22; tunnel leads to valve GG",
+        "Valve II has flow rate=0; tunnels lead to valves AA, JJ",
+        "Valve JJ has flow rate=21; tunnel leads to valve II",
+    ];
+
+    #[test]
+    fn test_parse() {
+        let valves = parse(&EXAMPLE).unwrap();
+        assert_eq!(valves.flows, vec![13, 2, 20, 3, 22, 21]);
+        // AA to HH: DD, EE, FF, GG, HH
+        assert_eq!(valves.distances[valves.start()][4], 5);
+    }
+
+    #[test]
+    fn test_solve() {
+        let valves = parse(&EXAMPLE).unwrap();
+        assert_eq!(solve(&valves), 1651);
+    }
+
+    #[test]
+    fn test_part2() {
+        let valves = parse(&EXAMPLE).unwrap();
+        assert_eq!(part2(&valves), 1707);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let input = ["Valve AA has flow rate=0; tunnels lead to valves BB, CC"];
+        let error = parse(&input).err().unwrap();
+        let expected = "a valve defined in the input";
+        assert_eq!(error, ParseError::new(16, 1, 50, expected, "BB,"));
+
+        let input = ["Valve BB has flow rate=x; tunnel leads to valve BB"];
+        let error = parse(&input).err().unwrap();
+        assert_eq!(error, ParseError::new(16, 1, 24, "a number", "x;"));
+
+        let input = ["Valve BB has flow rate=1; tunnel leads to valve BB"];
+        let error = parse(&input).err().unwrap();
+        assert_eq!(error, ParseError::new(16, 2, 1, "valve AA", "end of input"));
+
+        let input = [
+            "Valve AA has flow rate=0; tunnel leads to valve BB",
+            "Valve BB has flow rate=1; tunnel leads to valve AA",
+            "Valve AA has flow rate=2; tunnel leads to valve BB",
+        ];
+        let error = parse(&input).err().unwrap();
+        let expected = "a valve not defined before";
+        assert_eq!(error, ParseError::new(16, 3, 7, expected, "AA"));
+    }
+}
@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use anyhow::{bail, Result};
+
+use crate::error::{ParseError, Scanner};
+use crate::solution::Solution;
+
+const WIDTH: usize = 7;
+const LEFT_WALL: u8 = 1;
+const RIGHT_WALL: u8 = 1 << (WIDTH - 1);
+
+// rows from the bottom up, bit n is column n; each appears 2 units from the left wall
+const ROCKS: [&[u8]; 5] = [
+    &[0b1111],
+    &[0b010, 0b111, 0b010],
+    &[0b111, 0b100, 0b100],
+    &[0b1, 0b1, 0b1, 0b1],
+    &[0b11, 0b11],
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Jet {
+    Left,
+    Right,
+}
+
+// what the next rocks will do depends only on this, so seeing it twice means a cycle
+#[derive(PartialEq, Eq, Hash)]
+struct Fingerprint {
+    rock: usize,
+    jet: usize,
+    // how far below the top the highest rock in each column is
+    surface: [usize; WIDTH],
+}
+
+pub struct Chamber<'a> {
+    rows: Vec<u8>,
+    jets: &'a [Jet],
+    jet: usize,
+    rock: usize,
+}
+
+impl<'a> Chamber<'a> {
+    pub fn new(jets: &'a [Jet]) -> Result<Self> {
+        if jets.is_empty() {
+            bail!("day 17: no jets to push the rocks");
+        }
+        Ok(Self {
+            rows: Vec::new(),
+            jets,
+            jet: 0,
+            rock: 0,
+        })
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn drop_rock(&mut self) {
+        let mut rock: Vec<u8> = ROCKS[self.rock].iter().map(|row| row << 2).collect();
+        self.rock = (self.rock + 1) % ROCKS.len();
+        let mut bottom = self.rows.len() + 3;
+        loop {
+            let pushed = self.push(&rock);
+            if self.fits(&pushed, bottom) {
+                rock = pushed;
+            }
+            if bottom == 0 || !self.fits(&rock, bottom - 1) {
+                break;
+            }
+            bottom -= 1;
+        }
+        for (i, row) in rock.iter().enumerate() {
+            if bottom + i == self.rows.len() {
+                self.rows.push(0);
+            }
+            self.rows[bottom + i] |= row;
+        }
+    }
+
+    fn push(&mut self, rock: &[u8]) -> Vec<u8> {
+        let jet = self.jets[self.jet];
+        self.jet = (self.jet + 1) % self.jets.len();
+        match jet {
+            Jet::Left if rock.iter().all(|row| row & LEFT_WALL == 0) => {
+                rock.iter().map(|row| row >> 1).collect()
+            }
+            Jet::Right if rock.iter().all(|row| row & RIGHT_WALL == 0) => {
+                rock.iter().map(|row| row << 1).collect()
+            }
+            _ => rock.to_vec(),
+        }
+    }
+
+    fn fits(&self, rock: &[u8], bottom: usize) -> bool {
+        rock.iter().enumerate().all(|(i, row)| {
+            self.rows
+                .get(bottom + i)
+                .is_none_or(|settled| settled & row == 0)
+        })
+    }
+
+    fn fingerprint(&self) -> Fingerprint {
+        let mut surface = [self.rows.len(); WIDTH];
+        for (col, depth) in surface.iter_mut().enumerate() {
+            let top = self.rows.iter().rev().position(|row| row & (1 << col) != 0);
+            if let Some(top) = top {
+                *depth = top;
+            }
+        }
+        Fingerprint {
+            rock: self.rock,
+            jet: self.jet,
+            surface,
+        }
+    }
+}
+
+impl fmt::Display for Chamber<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.rows.iter().rev() {
+            let cells: String = (0..WIDTH)
+                .map(|col| if row & (1 << col) != 0 { '#' } else { '.' })
+                .collect();
+            writeln!(f, "|{}|", cells)?;
+        }
+        write!(f, "+{}+", "-".repeat(WIDTH))
+    }
+}
+
+/// Height of the tower after `rocks` rocks, skipping ahead once the chamber repeats itself.
+pub fn height(jets: &[Jet], rocks: u64) -> Result<u64> {
+    let mut chamber = Chamber::new(jets)?;
+    let mut seen = HashMap::new();
+    let mut skipped = None;
+    let mut dropped = 0;
+    while dropped < rocks {
+        chamber.drop_rock();
+        dropped += 1;
+        if skipped.is_some() {
+            continue;
+        }
+        let height = chamber.height() as u64;
+        let previous = seen.insert(chamber.fingerprint(), (dropped, height));
+        if let Some((before, height_before)) = previous {
+            let period = dropped - before;
+            let cycles = (rocks - dropped) / period;
+            dropped += cycles * period;
+            skipped = Some(cycles * (height - height_before));
+        }
+    }
+    Ok(chamber.height() as u64 + skipped.unwrap_or(0))
+}
+
+pub fn parse(input: &[&str]) -> Result<Vec<Jet>, ParseError> {
+    if input.len() != 1 {
+        let found = input.get(1).copied().unwrap_or("end of input");
+        let line = input.len().clamp(1, 2);
+        return Err(ParseError::new(
+            Day17::DAY,
+            line,
+            1,
+            "one line of jets",
+            found,
+        ));
+    }
+    let mut scanner = Scanner::new(Day17::DAY, 0, input[0]);
+    let mut jets = Vec::new();
+    loop {
+        jets.push(scanner.char_map("'<' or '>'", |c| match c {
+            '<' => Some(Jet::Left),
+            '>' => Some(Jet::Right),
+            _ => None,
+        })?);
+        if scanner.is_empty() {
+            return Ok(jets);
+        }
+    }
+}
+
+pub struct Day17;
+
+impl Solution for Day17 {
+    const YEAR: u32 = 2022;
+    const DAY: u32 = 17;
+
+    type Input = Vec<Jet>;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let input: Vec<&str> = input.lines().collect();
+        Ok(parse(&input)?)
+    }
+
+    fn part1(jets: &Self::Input) -> Result<u64> {
+        height(jets, 2022)
+    }
+
+    fn part2(jets: &Self::Input) -> Result<u64> {
+        height(jets, 1_000_000_000_000)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = ">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>";
+
+    #[test]
+    fn test_solve() {
+        let jets = parse(&[EXAMPLE]).unwrap();
+        assert_eq!(height(&jets, 2022).unwrap(), 3068);
+    }
+
+    #[test]
+    fn test_part2() {
+        let jets = parse(&[EXAMPLE]).unwrap();
+        assert_eq!(height(&jets, 1_000_000_000_000).unwrap(), 1514285714288);
+    }
+
+    #[test]
+    fn test_render() {
+        let jets = parse(&[EXAMPLE]).unwrap();
+        let mut chamber = Chamber::new(&jets).unwrap();
+        for _ in 0..3 {
+            chamber.drop_rock();
+        }
+        let expected = [
+            "|..#....|",
+            "|..#....|",
+            "|####...|",
+            "|..###..|",
+            "|...#...|",
+            "|..####.|",
+            "+-------+",
+        ];
+        assert_eq!(chamber.to_string(), expected.join("\n"));
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let error = parse(&[">><x<"]).err().unwrap();
+        assert_eq!(error, ParseError::new(17, 1, 4, "'<' or '>'", "x<"));
+
+        let error = parse(&[">><", ""]).err().unwrap();
+        assert_eq!(error, ParseError::new(17, 2, 1, "one line of jets", ""));
+
+        let error = height(&[], 2022).unwrap_err();
+        assert_eq!(error.to_string(), "day 17: no jets to push the rocks");
+    }
+}
@@ -1,6 +1,12 @@
-use std::collections::{HashSet, VecDeque};
+use std::collections::HashSet;
 
-type Voxel = (i32, i32, i32);
+use anyhow::Result;
+
+use crate::error::{ParseError, Scanner};
+use crate::search;
+use crate::solution::Solution;
+
+pub type Voxel = (i32, i32, i32);
 
 fn adjacent((x, y, z): &Voxel) -> Vec<Voxel> {
     vec![
@@ -33,22 +39,11 @@ fn border(cubes: &HashSet<Voxel>) -> Voxel {
 
 pub fn part2(lava: &HashSet<Voxel>) -> u32 {
     let border = border(lava);
-    let mut shell = HashSet::new();
-    let mut queue = VecDeque::new();
-    queue.push_back((0, 0, 0));
-    shell.insert((0, 0, 0));
-    while !queue.is_empty() {
-        let current = queue.pop_front().unwrap();
-        let adjacents = adjacent(&current);
-        for adjacent in adjacents {
-            if is_inside_border(&border, &adjacent)
-                && !lava.contains(&adjacent)
-                && shell.insert(adjacent)
-            {
-                queue.push_back(adjacent);
-            }
-        }
-    }
+    let shell = search::reachable([(0, 0, 0)], |current| {
+        adjacent(current)
+            .into_iter()
+            .filter(|voxel| is_inside_border(&border, voxel) && !lava.contains(voxel))
+    });
     lava
         .iter()
         .flat_map(adjacent)
@@ -74,6 +69,44 @@ pub fn solve(lava: &HashSet<Voxel>) -> u32 {
     result
 }
 
+pub fn parse(input: Vec<&str>) -> Result<HashSet<Voxel>, ParseError> {
+    let mut result = HashSet::new();
+    for (i, line) in input.into_iter().enumerate() {
+        let mut scanner = Scanner::new(Day18::DAY, i, line);
+        let x = scanner.number()?;
+        scanner.tag(",")?;
+        let y = scanner.number()?;
+        scanner.tag(",")?;
+        let z = scanner.number()?;
+        scanner.end()?;
+        result.insert((x, y, z));
+    }
+    Ok(result)
+}
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    const YEAR: u32 = 2022;
+    const DAY: u32 = 18;
+
+    type Input = HashSet<Voxel>;
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(parse(input.lines().collect())?)
+    }
+
+    fn part1(lava: &Self::Input) -> Result<u32> {
+        Ok(solve(lava))
+    }
+
+    fn part2(lava: &Self::Input) -> Result<u32> {
+        Ok(part2(lava))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -85,7 +118,7 @@ mod tests {
             "2,2,2", "1,2,2", "3,2,2", "2,1,2", "2,3,2", "2,2,1", "2,2,3", "2,2,4", "2,2,6",
             "1,2,5", "3,2,5", "2,1,5", "2,3,5",
         ];
-        assert_eq!(solve(&parse(input)), 64);
+        assert_eq!(solve(&parse(input).unwrap()), 64);
     }
 
     #[test]
@@ -94,32 +127,26 @@ mod tests {
             "2,2,2", "1,2,2", "3,2,2", "2,1,2", "2,3,2", "2,2,1", "2,2,3", "2,2,4", "2,2,6",
             "1,2,5", "3,2,5", "2,1,5", "2,3,5",
         ];
-        assert_eq!(part2(&parse(input)), 58);
+        assert_eq!(part2(&parse(input).unwrap()), 58);
     }
 
     #[test]
     fn test_part2_with_real_data() {
         let input = util::read_real_data("day18");
         let input = input.iter().map(|line| line.as_str()).collect();
-        assert_eq!(part2(&parse(input)), 2044);
+        assert_eq!(part2(&parse(input).unwrap()), 2044);
     }
 
     #[test]
     fn test_with_real_data() {
         let input = util::read_real_data("day18");
         let input = input.iter().map(|line| line.as_str()).collect();
-        assert_eq!(solve(&parse(input)), 3448);
+        assert_eq!(solve(&parse(input).unwrap()), 3448);
     }
 
-    fn parse(input: Vec<&str>) -> HashSet<Voxel> {
-        let mut result = HashSet::new();
-        for line in input {
-            let mut tokens = line.split(',');
-            let x = tokens.next().unwrap().parse().unwrap();
-            let y = tokens.next().unwrap().parse().unwrap();
-            let z = tokens.next().unwrap().parse().unwrap();
-            result.insert((x, y, z));
-        }
-        result
+    #[test]
+    fn test_parse_error() {
+        let error = parse(vec!["2,2,2", "1,2"]).unwrap_err();
+        assert_eq!(error, ParseError::new(18, 2, 4, "\",\"", "end of line"));
     }
 }
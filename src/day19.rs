@@ -1,7 +1,10 @@
+use anyhow::Result;
 use rayon::prelude::*;
-use regex::Regex;
 use std::collections::HashSet;
 
+use crate::error::{ParseError, Scanner};
+use crate::solution::Solution;
+
 const GEODE: usize = 3;
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash)]
@@ -88,52 +91,92 @@ fn estimate(blueprint: &Blueprint, t_max: i32) -> i32 {
     result
 }
 
-pub fn solve(input: &[&str]) -> i32 {
-    input
+pub fn solve(input: &[&str]) -> Result<i32> {
+    Ok(quality_levels(&parse(input)?))
+}
+
+fn quality_levels(blueprints: &[Blueprint]) -> i32 {
+    blueprints
         .par_iter()
-        .map(|line| Blueprint::new(line))
-        .map(|blueprint| blueprint.index * estimate(&blueprint, 24))
+        .map(|blueprint| blueprint.index * estimate(blueprint, 24))
         .sum::<i32>()
 }
 
-pub fn part2(input: &[&str]) -> i32 {
-    input
+pub fn part2(input: &[&str]) -> Result<i32> {
+    Ok(first_geodes(&parse(&input[..input.len().min(3)])?))
+}
+
+// only the first three blueprints survive the elephants
+fn first_geodes(blueprints: &[Blueprint]) -> i32 {
+    blueprints[..blueprints.len().min(3)]
         .par_iter()
-        .take(3)
-        .map(|line| Blueprint::new(line))
-        .map(|blueprint| estimate(&blueprint, 32))
+        .map(|blueprint| estimate(blueprint, 32))
         .product()
 }
 
-struct Blueprint {
+fn parse(input: &[&str]) -> Result<Vec<Blueprint>, ParseError> {
+    input
+        .iter()
+        .enumerate()
+        .map(|(i, line)| Blueprint::new(i, line))
+        .collect()
+}
+
+pub struct Blueprint {
     index: i32,
     prices: [[i32; 3]; 4],
 }
 
 impl Blueprint {
-    fn new(raw: &str) -> Self {
-        let regex = Regex::new(r"[^0-9]+").unwrap();
-        let only_numbers = regex.replace_all(raw, " ");
-        let tokens: Vec<i32> = only_numbers
-            .trim()
-            .split(' ')
-            .map(|token| token.parse())
-            .map(|token| token.unwrap())
-            .collect();
-        let index = tokens[0];
-        let ore_robot_ore = tokens[1];
-        let clay_robot_ore = tokens[2];
-        let obsidian_robot_ore = tokens[3];
-        let obsidian_robot_clay = tokens[4];
-        let geode_robot_ore = tokens[5];
-        let geode_robot_obsidian = tokens[6];
+    fn new(i: usize, raw: &str) -> Result<Self, ParseError> {
+        let mut scanner = Scanner::new(Day19::DAY, i, raw);
+        scanner.tag("Blueprint ")?;
+        let index = scanner.number()?;
+        scanner.tag(": Each ore robot costs ")?;
+        let ore_robot_ore = scanner.number()?;
+        scanner.tag(" ore. Each clay robot costs ")?;
+        let clay_robot_ore = scanner.number()?;
+        scanner.tag(" ore. Each obsidian robot costs ")?;
+        let obsidian_robot_ore = scanner.number()?;
+        scanner.tag(" ore and ")?;
+        let obsidian_robot_clay = scanner.number()?;
+        scanner.tag(" clay. Each geode robot costs ")?;
+        let geode_robot_ore = scanner.number()?;
+        scanner.tag(" ore and ")?;
+        let geode_robot_obsidian = scanner.number()?;
+        scanner.tag(" obsidian.")?;
+        scanner.end()?;
         let prices = [
             [ore_robot_ore, 0, 0],
             [clay_robot_ore, 0, 0],
             [obsidian_robot_ore, obsidian_robot_clay, 0],
             [geode_robot_ore, 0, geode_robot_obsidian],
         ];
-        Self { index, prices }
+        Ok(Self { index, prices })
+    }
+}
+
+pub struct Day19;
+
+impl Solution for Day19 {
+    const YEAR: u32 = 2022;
+    const DAY: u32 = 19;
+
+    type Input = Vec<Blueprint>;
+    type Part1 = i32;
+    type Part2 = i32;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let input: Vec<&str> = input.lines().collect();
+        Ok(parse(&input)?)
+    }
+
+    fn part1(blueprints: &Self::Input) -> Result<i32> {
+        Ok(quality_levels(blueprints))
+    }
+
+    fn part2(blueprints: &Self::Input) -> Result<i32> {
+        Ok(first_geodes(blueprints))
     }
 }
 
@@ -156,19 +199,26 @@ mod tests {
     fn test_with_real_data() {
         let input = util::read_real_data("day19");
         let input: Vec<&str> = input.iter().map(|line| line.as_str()).collect();
-        assert_eq!(solve(&input), 1834);
+        assert_eq!(solve(&input).unwrap(), 1834);
     }
 
     #[test]
     fn test_part2_with_real_data() {
         let input = util::read_real_data("day19");
         let input: Vec<&str> = input.iter().map(|line| line.as_str()).collect();
-        assert_eq!(part2(&input), 2128);
+        assert_eq!(part2(&input).unwrap(), 2128);
     }
 
     #[test]
     fn test_solve() {
         let input = [ "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.", "Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsidian robot costs 3 ore and 8 clay. Each geode robot costs 3 ore and 12 obsidian."];
-        assert_eq!(solve(&input), 33);
+        assert_eq!(solve(&input).unwrap(), 33);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let input = "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs two ore.";
+        let error = Blueprint::new(0, input).err().unwrap();
+        assert_eq!(error, ParseError::new(19, 1, 64, "a number", "two"));
     }
 }
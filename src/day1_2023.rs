@@ -1,26 +1,75 @@
-pub fn day1<S: AsRef<str>>(input: &[S]) -> u32 {
-    input
-        .iter()
-        .map(|line| line.as_ref())
-        .map(find_calibration_value)
-        .sum()
+use anyhow::Result;
+
+use crate::error::ParseError;
+use crate::solution::Solution;
+
+const WORDS: [&str; 9] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+pub fn day1<S: AsRef<str>>(input: &[S]) -> Result<u32> {
+    calibrate(input, digit)
 }
 
-fn find_calibration_value(line: &str) -> u32 {
+pub fn part2<S: AsRef<str>>(input: &[S]) -> Result<u32> {
+    calibrate(input, digit_or_word)
+}
+
+fn calibrate<S: AsRef<str>>(input: &[S], digit_at: fn(&str) -> Option<u32>) -> Result<u32> {
     let mut result = 0;
-    for char in line.chars() {
-        if char.is_numeric() {
-            result = char.to_digit(10).unwrap() * 10;
-            break;
-        }
+    for (i, line) in input.iter().enumerate() {
+        let line = line.as_ref();
+        result += find_calibration_value(line, digit_at)
+            .ok_or_else(|| ParseError::new(Day1::DAY, i + 1, 1, "a digit", line))?;
     }
-    for char in line.chars().rev() {
-        if char.is_numeric() {
-            result += char.to_digit(10).unwrap();
-            break;
-        }
+    Ok(result)
+}
+
+// digits are looked for at every position from both ends, so overlapping words like
+// "eightwo" give 8 as the first digit and 2 as the last
+fn find_calibration_value(line: &str, digit_at: fn(&str) -> Option<u32>) -> Option<u32> {
+    let first = line
+        .char_indices()
+        .find_map(|(i, _)| digit_at(&line[i..]))?;
+    let last = line
+        .char_indices()
+        .rev()
+        .find_map(|(i, _)| digit_at(&line[i..]))?;
+    Some(first * 10 + last)
+}
+
+fn digit(text: &str) -> Option<u32> {
+    text.chars().next()?.to_digit(10)
+}
+
+fn digit_or_word(text: &str) -> Option<u32> {
+    digit(text).or_else(|| {
+        let i = WORDS.iter().position(|word| text.starts_with(word))?;
+        Some(i as u32 + 1)
+    })
+}
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    const YEAR: u32 = 2023;
+    const DAY: u32 = 1;
+
+    type Input = Vec<String>;
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(input.lines().map(|line| line.to_owned()).collect())
+    }
+
+    fn part1(input: &Self::Input) -> Result<u32> {
+        day1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Result<u32> {
+        part2(input)
     }
-    result
 }
 
 #[cfg(test)]
@@ -32,19 +81,45 @@ mod tests {
     #[test]
     fn test_day1() {
         let input = vec!["1abc2", "pqr3stu8vwx", "a1b2c3d4e5f", "treb7uchet"];
-        let actual = day1(&input);
+        let actual = day1(&input).unwrap();
         assert_eq!(142, actual);
     }
 
     #[test]
     fn test_with_real_data() {
         let input = read_real_data("day1_2023");
-        let actual = day1(&input);
-        assert_eq!(123, actual);
+        let actual = day1(&input).unwrap();
+        assert_eq!(53080, actual);
     }
 
     #[test]
     fn test_find_calibration_value() {
-        assert_eq!(12, find_calibration_value("1abc2"));
+        assert_eq!(Some(12), find_calibration_value("1abc2", digit));
+        assert_eq!(None, find_calibration_value("abc", digit));
+        assert_eq!(Some(82), find_calibration_value("eightwo", digit_or_word));
+        assert_eq!(Some(11), find_calibration_value("oneight1", digit));
+        assert_eq!(Some(18), find_calibration_value("1oneight", digit_or_word));
+        assert_eq!(Some(77), find_calibration_value("xsevenx", digit_or_word));
+        assert_eq!(None, find_calibration_value("xsevnx", digit_or_word));
+    }
+
+    #[test]
+    fn test_part2() {
+        let input = [
+            "two1nine",
+            "eightwothree",
+            "abcone2threexyz",
+            "xtwone3four",
+            "4nineeightseven2",
+            "zoneight234",
+            "7pqrstsixteen",
+        ];
+        assert_eq!(281, part2(&input).unwrap());
+    }
+
+    #[test]
+    fn test_part2_with_real_data() {
+        let input = read_real_data("day1_2023");
+        assert_eq!(53268, part2(&input).unwrap());
     }
 }
@@ -1,5 +1,10 @@
+use anyhow::Result;
+
+use crate::error::{ParseError, Scanner};
+use crate::solution::Solution;
+
 #[derive(PartialEq, Copy, Clone)]
-enum Pick {
+pub enum Pick {
     Rock,
     Paper,
     Scissors,
@@ -12,12 +17,12 @@ enum Outcome {
 }
 
 impl Outcome {
-    fn from_guide(pick: char) -> Self {
+    fn from_guide(pick: char) -> Option<Self> {
         match pick {
-            'X' => Outcome::Lose,
-            'Y' => Outcome::Draw,
-            'Z' => Outcome::Win,
-            _ => panic!("from_guide: wrong guide {}", pick),
+            'X' => Some(Outcome::Lose),
+            'Y' => Some(Outcome::Draw),
+            'Z' => Some(Outcome::Win),
+            _ => None,
         }
     }
 }
@@ -31,21 +36,21 @@ impl Pick {
         }
     }
 
-    fn from_his_pick(pick: char) -> Self {
+    fn from_his_pick(pick: char) -> Option<Self> {
         match pick {
-            'A' => Pick::Rock,
-            'B' => Pick::Paper,
-            'C' => Pick::Scissors,
-            _ => panic!("from_his_pack: wrong pick: {}", pick),
+            'A' => Some(Pick::Rock),
+            'B' => Some(Pick::Paper),
+            'C' => Some(Pick::Scissors),
+            _ => None,
         }
     }
 
-    fn from_your_pick(pick: char) -> Self {
+    fn from_your_pick(pick: char) -> Option<Self> {
         match pick {
-            'X' => Pick::Rock,
-            'Y' => Pick::Paper,
-            'Z' => Pick::Scissors,
-            _ => panic!("from_your_pick: wrong picK: {}", pick),
+            'X' => Some(Pick::Rock),
+            'Y' => Some(Pick::Paper),
+            'Z' => Some(Pick::Scissors),
+            _ => None,
         }
     }
 }
@@ -68,32 +73,44 @@ fn score_for_round(him: Pick, you: Pick) -> u32 {
     }
 }
 
-pub fn day2<S: AsRef<str>>(input: &Vec<S>) -> u32 {
-    let mut total = 0;
-    for line in input {
-        let line = line.as_ref();
-        let mut chars = line.chars();
-        let him = chars.next().unwrap();
-        let him = Pick::from_his_pick(him);
-        let you = chars.nth(1).unwrap();
-        let you = Pick::from_your_pick(you);
-        total += score(him, you);
-    }
-    total
+pub fn day2<S: AsRef<str>>(input: &[S]) -> Result<u32> {
+    Ok(total_score(&parse(input)?))
+}
+
+pub fn part2<S: AsRef<str>>(input: &[S]) -> Result<u32> {
+    Ok(total_score_part2(&parse(input)?))
+}
+
+// the second column is only read as a pick or an outcome once we know which part it is
+fn total_score(rounds: &[(Pick, char)]) -> u32 {
+    rounds
+        .iter()
+        .map(|(him, you)| score(*him, Pick::from_your_pick(*you).unwrap()))
+        .sum()
+}
+
+fn total_score_part2(rounds: &[(Pick, char)]) -> u32 {
+    rounds
+        .iter()
+        .map(|(him, guide)| score_part2(*him, Outcome::from_guide(*guide).unwrap()))
+        .sum()
 }
 
-pub fn part2<S: AsRef<str>>(input: &Vec<S>) -> u32 {
-    let mut total = 0;
-    for line in input {
-        let line = line.as_ref();
-        let mut chars = line.chars();
-        let him = chars.next().unwrap();
-        let him = Pick::from_his_pick(him);
-        let outcome = chars.nth(1).unwrap();
-        let outcome = Outcome::from_guide(outcome);
-        total += score_part2(him, outcome);
+fn parse<S: AsRef<str>>(input: &[S]) -> Result<Vec<(Pick, char)>, ParseError> {
+    let mut result = Vec::new();
+    for (i, line) in input.iter().enumerate() {
+        result.push(parse_round(i, line.as_ref())?);
     }
-    total
+    Ok(result)
+}
+
+fn parse_round(i: usize, line: &str) -> Result<(Pick, char), ParseError> {
+    let mut scanner = Scanner::new(Day2::DAY, i, line);
+    let him = scanner.char_map("A, B or C", Pick::from_his_pick)?;
+    scanner.tag(" ")?;
+    let second = scanner.char_map("X, Y or Z", |c| matches!(c, 'X' | 'Y' | 'Z').then_some(c))?;
+    scanner.end()?;
+    Ok((him, second))
 }
 
 fn score_part2(him: Pick, outcome: Outcome) -> u32 {
@@ -113,6 +130,30 @@ fn translate_outcome(him: &Pick, outcome: &Outcome) -> Pick {
     }
 }
 
+pub struct Day2;
+
+impl Solution for Day2 {
+    const YEAR: u32 = 2022;
+    const DAY: u32 = 2;
+
+    type Input = Vec<(Pick, char)>;
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let input: Vec<&str> = input.lines().collect();
+        Ok(parse(&input)?)
+    }
+
+    fn part1(rounds: &Self::Input) -> Result<u32> {
+        Ok(total_score(rounds))
+    }
+
+    fn part2(rounds: &Self::Input) -> Result<u32> {
+        Ok(total_score_part2(rounds))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -120,25 +161,34 @@ mod tests {
 
     #[test]
     fn test_day2() {
-        let actual = day2(&vec!["A Y", "B X", "C Z"]);
+        let actual = day2(&["A Y", "B X", "C Z"]).unwrap();
         assert_eq!(actual, 15);
     }
 
     #[test]
     fn test_with_real_data() {
-        let actual = day2(&read_real_data("day2"));
+        let actual = day2(&read_real_data("day2")).unwrap();
         assert_eq!(actual, 9177, "actual is {}", actual);
     }
 
+    #[test]
+    fn test_parse_error() {
+        let error = part2(&["A Y", "B W"]).unwrap_err();
+        assert_eq!(
+            error.downcast::<ParseError>().unwrap(),
+            ParseError::new(2, 2, 3, "X, Y or Z", "W")
+        );
+    }
+
     #[test]
     fn test_part2() {
-        let actual = part2(&vec!["A Y", "B X", "C Z"]);
+        let actual = part2(&["A Y", "B X", "C Z"]).unwrap();
         assert_eq!(actual, 12);
     }
 
     #[test]
     fn test_part2_with_real_data() {
-        let actual = part2(&read_real_data("day2"));
+        let actual = part2(&read_real_data("day2")).unwrap();
         assert_eq!(actual, 12111, "actual is {}", actual);
     }
 }
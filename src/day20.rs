@@ -1,45 +1,128 @@
-use std::collections::VecDeque;
+use anyhow::{anyhow, Result};
 
-pub fn solve(data: &mut VecDeque<i32>) -> i32 {
-    let order: Vec<i32> = data.iter().copied().collect();
-    for current in order.iter() {
-        permute(data, *current);
+use crate::error::Scanner;
+use crate::solution::Solution;
+
+pub const DECRYPTION_KEY: i64 = 811589153;
+
+// circular doubly linked list over the original positions, so equal values stay apart
+struct Ring {
+    values: Vec<i64>,
+    next: Vec<usize>,
+    prev: Vec<usize>,
+}
+
+impl Ring {
+    fn new(values: Vec<i64>) -> Self {
+        let len = values.len();
+        Self {
+            values,
+            next: (0..len).map(|i| (i + 1) % len).collect(),
+            prev: (0..len).map(|i| (i + len - 1) % len).collect(),
+        }
+    }
+
+    // moves the element that started at index `i` by its value
+    fn permute(&mut self, i: usize) {
+        let len = self.values.len();
+        if len < 3 {
+            return;
+        }
+        // the others form a ring of len - 1, so going round it is no move at all
+        let shift = self.values[i].rem_euclid(len as i64 - 1) as usize;
+        if shift == 0 {
+            return;
+        }
+        let (prev, next) = (self.prev[i], self.next[i]);
+        self.next[prev] = next;
+        self.prev[next] = prev;
+
+        let mut after = prev;
+        if shift <= (len - 1) / 2 {
+            for _ in 0..shift {
+                after = self.next[after];
+            }
+        } else {
+            for _ in 0..len - 1 - shift {
+                after = self.prev[after];
+            }
+        }
+        let before = self.next[after];
+        self.next[after] = i;
+        self.prev[i] = after;
+        self.next[i] = before;
+        self.prev[before] = i;
+    }
+
+    fn mix(&mut self) {
+        for i in 0..self.values.len() {
+            self.permute(i);
+        }
+    }
+
+    // values in ring order, starting with the one that started at index `start`
+    fn values_from(&self, start: usize) -> Vec<i64> {
+        let mut result = Vec::with_capacity(self.values.len());
+        let mut current = start;
+        for _ in 0..self.values.len() {
+            result.push(self.values[current]);
+            current = self.next[current];
+        }
+        result
     }
-    at(data, 1000) + at(data, 2000) + at(data, 3000)
 }
 
-fn at(data: &VecDeque<i32>, index: usize) -> i32 {
-    let zero_index = index_of(data, 0);
-    data[(zero_index + index) % (data.len())]
+fn grove_coordinates(data: &[i64], key: i64, rounds: usize) -> Result<i64> {
+    let zero = data
+        .iter()
+        .position(|value| *value == 0)
+        .ok_or_else(|| anyhow!("day 20: the file has no 0"))?;
+    let mut ring = Ring::new(data.iter().map(|value| value * key).collect());
+    for _ in 0..rounds {
+        ring.mix();
+    }
+    let values = ring.values_from(zero);
+    Ok([1000, 2000, 3000]
+        .iter()
+        .map(|i| values[i % values.len()])
+        .sum())
 }
 
-fn index_of(data: &VecDeque<i32>, current: i32) -> usize {
-    let index = data.iter().position(|&x| x == current).unwrap();
-    index
+pub fn solve(data: &[i64]) -> Result<i64> {
+    grove_coordinates(data, 1, 1)
 }
 
-fn permute(data: &mut VecDeque<i32>, current: i32) {
-    if current == 0 {
-        return;
-    }
-    let pos = index_of(data, current);
-    data.remove(pos);
-    let mut pos = pos as i32 + current;
-    let len = data.len() as i32;
-    if pos < 0 {
-        while pos < 0 {
-            pos += len;
-        }
-    } else if pos > len {
-        while pos > len {
-	    pos -= len;
+pub fn part2(data: &[i64]) -> Result<i64> {
+    grove_coordinates(data, DECRYPTION_KEY, 10)
+}
+
+pub struct Day20;
+
+impl Solution for Day20 {
+    const YEAR: u32 = 2022;
+    const DAY: u32 = 20;
+
+    type Input = Vec<i64>;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let mut result = Vec::new();
+        for (i, line) in input.lines().enumerate() {
+            let mut scanner = Scanner::new(Self::DAY, i, line);
+            result.push(scanner.number()?);
+            scanner.end()?;
         }
+        Ok(result)
     }
-    let mut pos = pos as usize;
-    if pos == 0 {
-        pos = data.len();
+
+    fn part1(input: &Self::Input) -> Result<i64> {
+        solve(input)
+    }
+
+    fn part2(input: &Self::Input) -> Result<i64> {
+        part2(input)
     }
-    data.insert(pos, current);
 }
 
 #[cfg(test)]
@@ -49,43 +132,59 @@ mod tests {
 
     #[test]
     fn test_permute() {
-        let mut data: VecDeque<i32> = VecDeque::from([1, 2, -3, 3, -2, 0, 4]);
-        permute(&mut data, 1);
-        assert_eq!(data, VecDeque::from([2, 1, -3, 3, -2, 0, 4]));
-
-        permute(&mut data, 2);
-        assert_eq!(data, VecDeque::from([1, -3, 2, 3, -2, 0, 4]));
-
-        permute(&mut data, -3); // 1, -3, 2, 3, -2, 0, 4
-        assert_eq!(data, VecDeque::from([1, 2, 3, -2, -3, 0, 4]));
-
-        permute(&mut data, 3);
-        assert_eq!(data, VecDeque::from([1, 2, -2, -3, 0, 3, 4]));
-
-        permute(&mut data, -2); // 1, 2, -3, 0, 3, 4, -2
-        assert_eq!(data, VecDeque::from([1, 2, -3, 0, 3, 4, -2]));
-
-        permute(&mut data, 0);
-        assert_eq!(data, VecDeque::from([1, 2, -3, 0, 3, 4, -2]));
+        let mut ring = Ring::new(vec![1, 2, -3, 3, -2, 0, 4]);
+        // listed from the 1, which started at index 0
+        let expected: [[i64; 7]; 7] = [
+            [1, -3, 3, -2, 0, 4, 2],
+            [1, -3, 2, 3, -2, 0, 4],
+            [1, 2, 3, -2, -3, 0, 4],
+            [1, 2, -2, -3, 0, 3, 4],
+            [1, 2, -3, 0, 3, 4, -2],
+            [1, 2, -3, 0, 3, 4, -2],
+            [1, 2, -3, 4, 0, 3, -2],
+        ];
+        for (i, expected) in expected.iter().enumerate() {
+            ring.permute(i);
+            assert_eq!(ring.values_from(0), expected);
+        }
+    }
 
-        permute(&mut data, 4);
-        assert_eq!(data, VecDeque::from([1, 2, -3, 4, 0, 3, -2]));
+    #[test]
+    fn test_duplicates() {
+        // both 1s move, each exactly once
+        let mut ring = Ring::new(vec![1, 1, 0, 5]);
+        ring.mix();
+        assert_eq!(ring.values_from(2), vec![0, 1, 1, 5]);
     }
 
     #[test]
     fn test_solve() {
-        let mut data: VecDeque<i32> = VecDeque::from([1, 2, -3, 3, -2, 0, 4]);
-        assert_eq!(solve(&mut data), 3);
+        let data = [1, 2, -3, 3, -2, 0, 4];
+        assert_eq!(solve(&data).unwrap(), 3);
+        assert_eq!(part2(&data).unwrap(), 1623178306);
+    }
+
+    #[test]
+    fn test_without_zero() {
+        let error = solve(&[1, 2, -3]).unwrap_err();
+        assert_eq!(error.to_string(), "day 20: the file has no 0");
+
+        let error = Day20::parse("1\n2.5\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 20: line 2, column 2: expected end of line, found \".5\""
+        );
     }
 
     #[test]
     fn test_with_real_data() {
         let input = util::read_real_data("day20");
-        let mut input: VecDeque<i32> = input
+        let input: Vec<i64> = input
             .iter()
             .map(|line| line.parse())
             .map(|result| result.unwrap())
             .collect();
-        assert_eq!(solve(&mut input), 7462);
+        assert_eq!(solve(&input).unwrap(), 988);
+        assert_eq!(part2(&input).unwrap(), 7768531372516);
     }
 }
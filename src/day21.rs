@@ -1,41 +1,61 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::error::{ParseError, Scanner};
+use crate::solution::Solution;
 
 type Int = i128;
 
-enum Expr<'a> {
-    Calc(&'a str, Operation, &'a str),
+/// What each monkey yells, by name.
+pub type Registry = HashMap<String, Expr>;
+
+pub enum Expr {
+    Calc(String, Operation, String),
     Number(Int),
 }
 
-impl<'a> Expr<'a> {
-    fn eval(&self, registry: &HashMap<&str, Expr>) -> Int {
+impl Expr {
+    fn eval(&self, registry: &Registry) -> Result<Int> {
+        self.eval_visiting(registry, &mut HashSet::new())
+    }
+
+    // `visiting` holds the monkeys whose numbers are being worked out, so a monkey
+    // waiting on itself is an error rather than endless recursion
+    fn eval_visiting<'a>(
+        &'a self,
+        registry: &'a Registry,
+        visiting: &mut HashSet<&'a str>,
+    ) -> Result<Int> {
         match self {
-            Expr::Number(x) => *x,
+            Expr::Number(x) => Ok(*x),
             Expr::Calc(left, operation, right) => {
-                let left = registry.get(left).unwrap();
-                let right = registry.get(right).unwrap();
-                operation.calc(left.eval(registry), right.eval(registry))
+                let left = eval_monkey(left, registry, visiting)?;
+                let right = eval_monkey(right, registry, visiting)?;
+                operation.calc(left, right)
             }
         }
     }
 
-    fn new(expr: &'a str) -> Expr {
-        if expr.contains(' ') {
-            let mut tokens = expr.split(' ');
-            let left = tokens.next().unwrap();
-            let operation = tokens.next().unwrap();
-            let operation = Operation::new(operation);
-            let right = tokens.next().unwrap();
-            return Self::Calc(left, operation, right);
+    fn new(scanner: &mut Scanner) -> Result<Expr, ParseError> {
+        if !scanner.rest().contains(' ') {
+            let number = scanner.number()?;
+            scanner.end()?;
+            return Ok(Self::Number(number));
         }
-        Self::Number(expr.parse().unwrap())
+        let left = scanner.until(' ');
+        scanner.tag(" ")?;
+        let operation = Operation::new(scanner)?;
+        scanner.tag(" ")?;
+        let right = scanner.until(' ');
+        scanner.end()?;
+        Ok(Self::Calc(left.to_owned(), operation, right.to_owned()))
     }
 
-    fn is_depend_on_human(&self, registry: &HashMap<&str, Expr>) -> bool {
+    fn is_depend_on_human(&self, registry: &Registry) -> bool {
         match self {
             Expr::Number(_) => false,
-            Expr::Calc("humn", _, _) => true,
-            Expr::Calc(_, _, "humn") => true,
+            Expr::Calc(left, _, right) if left == "humn" || right == "humn" => true,
             Expr::Calc(left, _, right) => {
                 let left = registry.get(left).unwrap();
                 let right = registry.get(right).unwrap();
@@ -44,27 +64,27 @@ impl<'a> Expr<'a> {
         }
     }
 
-    fn adjust_human(&self, registry: &HashMap<&str, Expr>, operation_result: Int) -> Int {
+    fn adjust_human(&self, registry: &Registry, operation_result: Int) -> Result<Int> {
         match self {
-            Expr::Number(val) => panic!("number {}, target {}", val, operation_result),
-            Expr::Calc("humn", oper, right_name) => {
+            Expr::Number(val) => bail!("day 21: {} can't be made {}", val, operation_result),
+            Expr::Calc(left_name, oper, right_name) if left_name == "humn" => {
                 let right = registry.get(right_name).unwrap();
-                oper.reverse_left_var(right.eval(registry), operation_result)
+                oper.reverse_left_var(right.eval(registry)?, operation_result)
             }
-            Expr::Calc(left_name, oper, "humn") => {
+            Expr::Calc(left_name, oper, right_name) if right_name == "humn" => {
                 let left = registry.get(left_name).unwrap();
-                oper.reverse_right_var(left.eval(registry), operation_result)
+                oper.reverse_right_var(left.eval(registry)?, operation_result)
             }
             Expr::Calc(left_name, oper, right_name) => {
                 let left = registry.get(left_name).unwrap();
                 let right = registry.get(right_name).unwrap();
                 if left.is_depend_on_human(registry) {
-                    let right = right.eval(registry);
-                    let operation_result = oper.reverse_left_var(right, operation_result);
+                    let right = right.eval(registry)?;
+                    let operation_result = oper.reverse_left_var(right, operation_result)?;
                     left.adjust_human(registry, operation_result)
                 } else {
-                    let left = left.eval(registry);
-                    let operation_result = oper.reverse_right_var(left, operation_result);
+                    let left = left.eval(registry)?;
+                    let operation_result = oper.reverse_right_var(left, operation_result)?;
                     right.adjust_human(registry, operation_result)
                 }
             }
@@ -72,8 +92,26 @@ impl<'a> Expr<'a> {
     }
 }
 
+fn eval_monkey<'a>(
+    name: &'a str,
+    registry: &'a Registry,
+    visiting: &mut HashSet<&'a str>,
+) -> Result<Int> {
+    if !visiting.insert(name) {
+        bail!("day 21: {} depends on its own number", name);
+    }
+    let value = registry[name].eval_visiting(registry, visiting)?;
+    visiting.remove(name);
+    Ok(value)
+}
+
+fn div(left: Int, right: Int) -> Result<Int> {
+    left.checked_div(right)
+        .ok_or_else(|| anyhow!("day 21: {} / {} can't be calculated", left, right))
+}
+
 #[derive(Debug)]
-enum Operation {
+pub enum Operation {
     Add,
     Sub,
     Mul,
@@ -81,78 +119,133 @@ enum Operation {
 }
 
 impl Operation {
-    fn reverse_left_var(&self, right: Int, operation_result: Int) -> Int {
+    fn reverse_left_var(&self, right: Int, operation_result: Int) -> Result<Int> {
         match self {
-            Operation::Add => operation_result - right,
-            Operation::Sub => operation_result + right,
-            Operation::Mul => operation_result / right,
-            Operation::Div => operation_result * right,
+            Operation::Add => Ok(operation_result - right),
+            Operation::Sub => Ok(operation_result + right),
+            Operation::Mul => div(operation_result, right),
+            Operation::Div => Ok(operation_result * right),
         }
     }
 
-    fn reverse_right_var(&self, left: Int, operation_result: Int) -> Int {
+    fn reverse_right_var(&self, left: Int, operation_result: Int) -> Result<Int> {
         match self {
-            Operation::Add => operation_result - left,
-            Operation::Sub => left - operation_result,
-            Operation::Mul => operation_result / left,
-            Operation::Div => left / operation_result,
+            Operation::Add => Ok(operation_result - left),
+            Operation::Sub => Ok(left - operation_result),
+            Operation::Mul => div(operation_result, left),
+            Operation::Div => div(left, operation_result),
         }
     }
 
-    fn new(expr: &str) -> Self {
-        match expr {
-            "+" => Operation::Add,
-            "-" => Operation::Sub,
-            "*" => Operation::Mul,
-            "/" => Operation::Div,
-            _ => panic!("{} is not supported", expr),
-        }
+    fn new(scanner: &mut Scanner) -> Result<Self, ParseError> {
+        scanner.char_map("+, -, * or /", |c| match c {
+            '+' => Some(Operation::Add),
+            '-' => Some(Operation::Sub),
+            '*' => Some(Operation::Mul),
+            '/' => Some(Operation::Div),
+            _ => None,
+        })
     }
 
-    fn calc(&self, left: Int, right: Int) -> Int {
+    fn calc(&self, left: Int, right: Int) -> Result<Int> {
         match self {
-            Operation::Add => left + right,
-            Operation::Sub => left - right,
-            Operation::Mul => left * right,
-            Operation::Div => left / right,
+            Operation::Add => Ok(left + right),
+            Operation::Sub => Ok(left - right),
+            Operation::Mul => Ok(left * right),
+            Operation::Div => div(left, right),
         }
     }
 }
 
-pub fn solve(input: &Vec<&str>) -> Int {
-    let registry = parse_registry(input);
-    let root = registry.get("root");
-    root.unwrap().eval(&registry)
+pub fn solve(input: &Vec<&str>) -> Result<Int> {
+    root_number(&parse_registry(input)?)
 }
 
-fn parse_registry<'a>(input: &'a Vec<&'a str>) -> HashMap<&'a str, Expr> {
-    let mut registry: HashMap<&str, Expr> = HashMap::new();
-    for line in input {
-        let mut name_and_expr = line.split(": ");
-        let name = name_and_expr.next().unwrap();
-        let expr = name_and_expr.next().unwrap();
-        let expr = Expr::new(expr);
-        registry.insert(name, expr);
+fn root_number(registry: &Registry) -> Result<Int> {
+    if !registry.contains_key("root") {
+        bail!("day 21: no root monkey");
     }
-    registry
+    eval_monkey("root", registry, &mut HashSet::new())
 }
 
-pub fn part2(input: &Vec<&str>) -> Int {
-    let registry = parse_registry(input);
-
-    let root = registry.get("root").unwrap();
-    if let Expr::Calc(left_name, _, right_name) = root {
-        let left = registry.get(left_name).unwrap();
-        let right = registry.get(right_name).unwrap();
-        if left.is_depend_on_human(&registry) {
-            let target = right.eval(&registry);
-            return left.adjust_human(&registry, target);
-        } else {
-            let target = left.eval(&registry);
-            return right.adjust_human(&registry, target);
+// every monkey referenced in an expression must be defined somewhere,
+// so lookups while evaluating never fail
+fn parse_registry(input: &[&str]) -> Result<Registry, ParseError> {
+    let mut registry = Registry::new();
+    let mut references = Vec::new();
+    for (i, line) in input.iter().enumerate() {
+        let mut scanner = Scanner::new(Day21::DAY, i, line);
+        let name = scanner.until(':');
+        scanner.tag(": ")?;
+        let expr_start = scanner.clone();
+        let expr = Expr::new(&mut scanner)?;
+        if let Expr::Calc(left, _, right) = &expr {
+            references.push((expr_start.clone(), left.clone()));
+            let mut at_right = expr_start;
+            at_right.until(' ');
+            at_right.tag(" ")?;
+            at_right.char()?;
+            at_right.tag(" ")?;
+            references.push((at_right, right.clone()));
+        }
+        registry.insert(name.to_owned(), expr);
+    }
+    for (at, name) in references {
+        if !registry.contains_key(&name) {
+            return Err(at.error("a monkey defined in the input"));
         }
     }
-    unreachable!()
+    Ok(registry)
+}
+
+pub fn part2(input: &Vec<&str>) -> Result<Int> {
+    human_number(&parse_registry(input)?)
+}
+
+fn human_number(registry: &Registry) -> Result<Int> {
+    let root = registry
+        .get("root")
+        .ok_or_else(|| anyhow!("day 21: no root monkey"))?;
+    let Expr::Calc(left_name, _, right_name) = root else {
+        bail!("day 21: root monkey yells a number");
+    };
+    // rules out monkeys waiting on themselves, which the search for humn would follow forever
+    root_number(registry)?;
+    let left = registry.get(left_name).unwrap();
+    let right = registry.get(right_name).unwrap();
+    if left.is_depend_on_human(registry) {
+        let target = right.eval(registry)?;
+        left.adjust_human(registry, target)
+    } else if right.is_depend_on_human(registry) {
+        let target = left.eval(registry)?;
+        right.adjust_human(registry, target)
+    } else {
+        bail!("day 21: root does not depend on humn")
+    }
+}
+
+pub struct Day21;
+
+impl Solution for Day21 {
+    const YEAR: u32 = 2022;
+    const DAY: u32 = 21;
+
+    type Input = Registry;
+    type Part1 = Int;
+    type Part2 = Int;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let input: Vec<&str> = input.lines().collect();
+        Ok(parse_registry(&input)?)
+    }
+
+    fn part1(registry: &Self::Input) -> Result<Int> {
+        root_number(registry)
+    }
+
+    fn part2(registry: &Self::Input) -> Result<Int> {
+        human_number(registry)
+    }
 }
 
 #[cfg(test)]
@@ -179,7 +272,7 @@ mod tests {
             "drzm: hmdt - zczc",
             "hmdt: 32",
         ];
-        assert_eq!(solve(&input), 152);
+        assert_eq!(solve(&input).unwrap(), 152);
     }
 
     #[test]
@@ -201,20 +294,59 @@ mod tests {
             "drzm: hmdt - zczc",
             "hmdt: 32",
         ];
-        assert_eq!(part2(&input), 301);
+        assert_eq!(part2(&input).unwrap(), 301);
     }
 
     #[test]
     fn test_with_real_data() {
         let input = util::read_real_data("day21");
         let input = input.iter().map(|line| line.as_str()).collect();
-        assert_eq!(solve(&input), 85616733059734);
+        assert_eq!(solve(&input).unwrap(), 85616733059734);
     }
 
     #[test]
     fn test_part2_with_real_data() {
         let input = util::read_real_data("day21");
         let input = input.iter().map(|line| line.as_str()).collect();
-        assert_eq!(part2(&input), 3560324848168);
+        assert_eq!(part2(&input).unwrap(), 3560324848168);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let input = ["root: pppw % sjmn"];
+        let error = parse_registry(&input).err().unwrap();
+        assert_eq!(error, ParseError::new(21, 1, 12, "+, -, * or /", "%"));
+
+        let input = ["root: pppw + sjmn", "pppw: 3"];
+        let error = parse_registry(&input).err().unwrap();
+        let expected = "a monkey defined in the input";
+        assert_eq!(error, ParseError::new(21, 1, 14, expected, "sjmn"));
+
+        let input = vec!["root: 3"];
+        let error = part2(&input).unwrap_err();
+        assert_eq!(error.to_string(), "day 21: root monkey yells a number");
+    }
+
+    #[test]
+    fn test_eval_error() {
+        let input = vec![
+            "root: pppw / sjmn",
+            "pppw: 3",
+            "sjmn: humn - humn",
+            "humn: 5",
+        ];
+        let error = solve(&input).unwrap_err();
+        assert_eq!(error.to_string(), "day 21: 3 / 0 can't be calculated");
+
+        let input = vec![
+            "root: pppw + sjmn",
+            "pppw: sjmn * humn",
+            "sjmn: pppw - humn",
+            "humn: 5",
+        ];
+        let error = solve(&input).unwrap_err();
+        assert_eq!(error.to_string(), "day 21: pppw depends on its own number");
+        let error = part2(&input).unwrap_err();
+        assert_eq!(error.to_string(), "day 21: pppw depends on its own number");
     }
 }
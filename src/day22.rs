@@ -1,109 +1,63 @@
-use std::collections::VecDeque;
+use anyhow::{anyhow, Result};
 
-struct Grid {
-    grid: Vec<Vec<char>>,
-}
+use crate::error::{ParseError, Scanner};
+use crate::grid::{Grid, Pos};
+use crate::solution::Solution;
 
-impl Grid {
-    fn min_wrap_col(&self, col: usize) -> (usize, usize) {
-        let column: Vec<char> = self.col(col);
-        for (i, point) in column.iter().enumerate() {
-            if *point != ' ' {
-                return (col, i);
-            }
-        }
-        unreachable!()
-    }
+// the map with rows padded by ' ' to the widest one
+struct Board {
+    grid: Grid<char>,
+}
 
-    fn max_wrap_col(&self, col: usize) -> (usize, usize) {
-        let column: Vec<char> = self.col(col);
-        for i in (0..column.len()).rev() {
-            if column[i] != ' ' {
-                return (col, i);
-            }
-        }
-        unreachable!()
+impl Board {
+    fn min_wrap_col(&self, col: i32) -> Pos {
+        let row = self.grid.col(col as usize).position(|point| *point != ' ');
+        Pos::new(col, row.unwrap() as i32)
     }
 
-    fn max_wrap_row(&self, row: usize) -> (usize, usize) {
-        let full_row = &self.grid[row];
-        for i in (0..full_row.len()).rev() {
-            if full_row[i] != ' ' {
-                return (i, row);
-            }
-        }
-        unreachable!()
+    fn max_wrap_col(&self, col: i32) -> Pos {
+        let row = self.grid.col(col as usize).rposition(|point| *point != ' ');
+        Pos::new(col, row.unwrap() as i32)
     }
 
-    fn min_wrap_row(&self, row: usize) -> (usize, usize) {
-        let full_row = &self.grid[row];
-        for (i, point) in full_row.iter().enumerate() {
-            if *point != ' ' {
-                return (i, row);
-            }
-        }
-        unreachable!()
+    fn max_wrap_row(&self, row: i32) -> Pos {
+        let full_row = self.grid.row(row as usize);
+        let col = full_row.iter().rposition(|point| *point != ' ');
+        Pos::new(col.unwrap() as i32, row)
     }
 
-    fn col(&self, col: usize) -> Vec<char> {
-        let mut result = Vec::new();
-        for line in self.grid.iter() {
-            if col >= line.len() {
-                result.push(' ');
-            } else {
-                result.push(line[col]);
-            }
-        }
-        result
+    fn min_wrap_row(&self, row: i32) -> Pos {
+        let full_row = self.grid.row(row as usize);
+        let col = full_row.iter().position(|point| *point != ' ');
+        Pos::new(col.unwrap() as i32, row)
     }
 
-    fn new(raw: &[&str]) -> Self {
-        let grid = raw
-            .iter()
-            .map(|line| line.chars())
-            .map(|chars| chars.collect())
-            .collect();
-        Self { grid }
+    fn new(raw: &[&str]) -> Result<Self, ParseError> {
+        let grid = Grid::parse_ragged(Day22::DAY, raw, "' ', '.' or '#'", ' ', |c| {
+            matches!(c, ' ' | '.' | '#').then_some(c)
+        })?;
+        Ok(Self { grid })
     }
 
-    fn is_wall(&self, col: usize, row: usize) -> bool {
-        if row >= self.grid.len() {
-            return false;
-        }
-        if col >= self.grid[row].len() {
-            return false;
-        }
-        self.grid[row][col] == '#'
+    fn is_wall(&self, pos: Pos) -> bool {
+        self.grid.get(pos) == Some(&'#')
     }
 
-    fn is_out_of_bounds(&self, col: isize, row: isize) -> bool {
-        if col < 0 || row < 0 {
-            return true;
-        }
-
-        if row >= self.grid.len() as isize {
-            return true;
-        }
-
-        let row = &self.grid[row as usize];
-        if col as usize >= row.len() {
-            return true;
-        }
-        row[col as usize] == ' '
+    fn is_out_of_bounds(&self, pos: Pos) -> bool {
+        self.grid.get(pos).is_none_or(|point| *point == ' ')
     }
 
-    fn find_start(&self) -> usize {
-        let topmost = &self.grid[0];
-        for (i, point) in topmost.iter().enumerate() {
-            if *point == '.' {
-                return i;
-            }
+    fn find_start(&self) -> Option<i32> {
+        if self.grid.height() == 0 {
+            return None;
         }
-        panic!()
+        let topmost = self.grid.row(0);
+        let col = topmost.iter().position(|point| *point == '.')?;
+        Some(col as i32)
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Direction {
     Up,
     Down,
@@ -111,17 +65,22 @@ enum Direction {
     Right,
 }
 
-impl From<char> for Direction {
-    fn from(c: char) -> Self {
+impl Direction {
+    const ALL: [Direction; 4] = [
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+        Direction::Up,
+    ];
+
+    fn from_turn(c: char) -> Option<Self> {
         match c {
-            'R' => Direction::Right,
-            'L' => Direction::Left,
-            _ => panic!("{} direction is not supported", c),
+            'R' => Some(Direction::Right),
+            'L' => Some(Direction::Left),
+            _ => None,
         }
     }
-}
 
-impl Direction {
     fn turn(&self, to: &Direction) -> Self {
         match (self, to) {
             (Direction::Up, Direction::Left) => Direction::Left,
@@ -135,6 +94,15 @@ impl Direction {
             (_, _) => panic!("turn from {:?} to {:?} is not supported", self, to),
         }
     }
+
+    fn offset(&self) -> Pos {
+        match self {
+            Direction::Up => Pos::UP,
+            Direction::Down => Pos::DOWN,
+            Direction::Left => Pos::LEFT,
+            Direction::Right => Pos::RIGHT,
+        }
+    }
 }
 
 enum Action {
@@ -142,29 +110,22 @@ enum Action {
     Turn(Direction),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 struct Cursor {
-    col: usize,
-    row: usize,
+    pos: Pos,
     direction: Direction,
 }
 
 impl Cursor {
     fn turn(&self, to: &Direction) -> Self {
         Self {
-            col: self.col,
-            row: self.row,
+            pos: self.pos,
             direction: self.direction.turn(to),
         }
     }
 
-    fn step(&self) -> (isize, isize) {
-        match self.direction {
-            Direction::Up => (self.col as isize, self.row as isize - 1),
-            Direction::Down => (self.col as isize, self.row as isize + 1),
-            Direction::Left => (self.col as isize - 1, self.row as isize),
-            Direction::Right => (self.col as isize + 1, self.row as isize),
-        }
+    fn step(&self) -> Pos {
+        self.pos + self.direction.offset()
     }
 
     fn evaluate(&self) -> usize {
@@ -174,24 +135,225 @@ impl Cursor {
             Direction::Left => 2,
             Direction::Up => 3,
         };
-        (self.row + 1) * 1000 + (self.col + 1) * 4 + score
+        (self.pos.row as usize + 1) * 1000 + (self.pos.col as usize + 1) * 4 + score
     }
 }
 
-struct Trip {
-    grid: Grid,
-    cursor: Cursor,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Flat,
+    Cube,
+}
+
+type Vec3 = [i32; 3];
+
+fn neg(v: Vec3) -> Vec3 {
+    [-v[0], -v[1], -v[2]]
+}
+
+// where a face of the net ends up on the cube: the directions its columns and rows grow in
+// and the direction it faces outwards
+#[derive(Debug, Clone, Copy)]
+struct Face {
+    right: Vec3,
+    down: Vec3,
+    normal: Vec3,
+}
+
+impl Face {
+    fn unfolded(&self, direction: Direction) -> Face {
+        let Face {
+            right,
+            down,
+            normal,
+        } = *self;
+        match direction {
+            Direction::Right => Face {
+                right: neg(normal),
+                down,
+                normal: right,
+            },
+            Direction::Left => Face {
+                right: normal,
+                down,
+                normal: neg(right),
+            },
+            Direction::Down => Face {
+                right,
+                down: neg(normal),
+                normal: down,
+            },
+            Direction::Up => Face {
+                right,
+                down: normal,
+                normal: neg(down),
+            },
+        }
+    }
+
+    fn heading(&self, direction: Direction) -> Vec3 {
+        match direction {
+            Direction::Right => self.right,
+            Direction::Left => neg(self.right),
+            Direction::Down => self.down,
+            Direction::Up => neg(self.down),
+        }
+    }
+}
+
+// the map folded along the edges between its faces, each with its column and row in the net
+struct Cube {
+    size: i32,
+    faces: Vec<(Pos, Face)>,
 }
 
-impl Trip {
-    fn new(input: &[&str]) -> Self {
-        let grid = Grid::new(&input[0..input.len() - 2]);
-        let cursor = Cursor {
-            col: grid.find_start(),
-            row: 0,
+impl Cube {
+    fn new(board: &Board) -> Result<Self> {
+        let tiles = board.grid.iter().filter(|(_, c)| **c != ' ').count();
+        let size = ((tiles / 6) as f64).sqrt() as i32;
+        let not_a_cube = || anyhow!("day {}: the map doesn't fold into a cube", Day22::DAY);
+        if size == 0 || (size * size * 6) as usize != tiles {
+            return Err(not_a_cube());
+        }
+        let is_face = |pos: Pos| {
+            let corner = Pos::new(pos.col * size, pos.row * size);
+            !board.is_out_of_bounds(corner)
+        };
+        let first = (0..board.grid.width() as i32 / size)
+            .map(|col| Pos::new(col, 0))
+            .find(|pos| is_face(*pos))
+            .ok_or_else(not_a_cube)?;
+        let mut faces = vec![(
+            first,
+            Face {
+                right: [1, 0, 0],
+                down: [0, 1, 0],
+                normal: [0, 0, -1],
+            },
+        )];
+        let mut i = 0;
+        while i < faces.len() {
+            let (pos, face) = faces[i];
+            for direction in Direction::ALL {
+                let next = pos + direction.offset();
+                if is_face(next) && !faces.iter().any(|(seen, _)| *seen == next) {
+                    faces.push((next, face.unfolded(direction)));
+                }
+            }
+            i += 1;
+        }
+        let mut normals: Vec<Vec3> = faces.iter().map(|(_, face)| face.normal).collect();
+        normals.sort();
+        normals.dedup();
+        if faces.len() != 6 || normals.len() != 6 {
+            return Err(not_a_cube());
+        }
+        Ok(Self { size, faces })
+    }
+
+    // leaves the face `cursor` is on over the edge it's facing
+    fn wrap(&self, cursor: &Cursor) -> Cursor {
+        let n = self.size;
+        let face_pos = Pos::new(cursor.pos.col / n, cursor.pos.row / n);
+        let local = cursor.pos - Pos::new(face_pos.col * n, face_pos.row * n);
+        let face = self.face(face_pos);
+        // position along the edge and the direction it's counted in
+        let (along, t) = match cursor.direction {
+            Direction::Right | Direction::Left => (face.down, local.row),
+            Direction::Down | Direction::Up => (face.right, local.col),
+        };
+
+        let heading = face.heading(cursor.direction);
+        let (target_pos, target) = *self
+            .faces
+            .iter()
+            .find(|(_, target)| target.normal == heading)
+            .unwrap();
+        let inwards = neg(face.normal);
+        let direction = Direction::ALL
+            .into_iter()
+            .find(|direction| target.heading(*direction) == inwards)
+            .unwrap();
+        let target_along = match direction {
+            Direction::Right | Direction::Left => target.down,
+            Direction::Down | Direction::Up => target.right,
+        };
+        let t = if target_along == along { t } else { n - 1 - t };
+        let local = match direction {
+            Direction::Right => Pos::new(0, t),
+            Direction::Left => Pos::new(n - 1, t),
+            Direction::Down => Pos::new(t, 0),
+            Direction::Up => Pos::new(t, n - 1),
+        };
+        Cursor {
+            pos: Pos::new(target_pos.col * n, target_pos.row * n) + local,
+            direction,
+        }
+    }
+
+    fn face(&self, pos: Pos) -> Face {
+        self.faces.iter().find(|(at, _)| *at == pos).unwrap().1
+    }
+}
+
+/// The map, where the path starts on it and the path itself.
+pub struct Notes {
+    board: Board,
+    start: Cursor,
+    commands: Commands,
+}
+
+impl Notes {
+    // input is the map, a blank line and the path, the path is parsed by `Commands`
+    fn new(input: &[&str]) -> Result<Self, ParseError> {
+        let map_len = input.len().saturating_sub(2);
+        if input.len() < 3 || !input[map_len].is_empty() {
+            let found = input.get(map_len).copied().unwrap_or("end of input");
+            let expected = "the map, a blank line and the path";
+            return Err(ParseError::new(Day22::DAY, map_len + 1, 1, expected, found));
+        }
+        let board = Board::new(&input[0..map_len])?;
+        let col = board
+            .find_start()
+            .ok_or_else(|| Scanner::new(Day22::DAY, 0, input[0]).error("an open tile"))?;
+        let start = Cursor {
+            pos: Pos::new(col, 0),
             direction: Direction::Right,
         };
-        Self { grid, cursor }
+        let commands = Commands::new(input)?;
+        Ok(Self {
+            board,
+            start,
+            commands,
+        })
+    }
+
+    fn password(&self, mode: Mode) -> Result<usize> {
+        let mut trip = Trip::new(self, mode)?;
+        for command in self.commands.state.iter() {
+            trip.action(command);
+        }
+        Ok(trip.password())
+    }
+}
+
+struct Trip<'a> {
+    board: &'a Board,
+    cube: Option<Cube>,
+    cursor: Cursor,
+}
+
+impl<'a> Trip<'a> {
+    fn new(notes: &'a Notes, mode: Mode) -> Result<Self> {
+        let cube = match mode {
+            Mode::Flat => None,
+            Mode::Cube => Some(Cube::new(&notes.board)?),
+        };
+        Ok(Self {
+            board: &notes.board,
+            cube,
+            cursor: notes.start,
+        })
     }
 
     fn action(&mut self, action: &Action) {
@@ -199,42 +361,33 @@ impl Trip {
             Action::Turn(to) => self.cursor = self.cursor.turn(to),
             Action::Go(distance) => {
                 for _ in 0..*distance {
-                    let (col, row) = self.cursor.step();
-                    if self.grid.is_out_of_bounds(col, row) {
-                        if let Some((c, r)) = self.wrap() {
-                            self.cursor = Cursor {
-                                col: c,
-                                row: r,
-                                direction: self.cursor.direction,
-                            };
-                            break;
-                        }
-                    }
-                    if self.grid.is_wall(col as usize, row as usize) {
+                    let next = self.next();
+                    if self.board.is_wall(next.pos) {
                         break;
                     }
-                    self.cursor = Cursor {
-                        col: col as usize,
-                        row: row as usize,
-                        direction: self.cursor.direction,
-                    };
+                    self.cursor = next;
                 }
             }
         }
     }
 
-    fn wrap(&mut self) -> Option<(usize, usize)> {
-        let target @ (col, row) = match self.cursor.direction {
-            Direction::Down => self.grid.min_wrap_col(self.cursor.col),
-            Direction::Up => self.grid.max_wrap_col(self.cursor.col),
-            Direction::Left => self.grid.max_wrap_row(self.cursor.row),
-            Direction::Right => self.grid.min_wrap_row(self.cursor.row),
-        };
-
-        if self.grid.is_wall(col, row) {
-            return None;
+    fn next(&self) -> Cursor {
+        let pos = self.cursor.step();
+        let direction = self.cursor.direction;
+        if !self.board.is_out_of_bounds(pos) {
+            return Cursor { pos, direction };
+        }
+        if let Some(cube) = &self.cube {
+            return cube.wrap(&self.cursor);
         }
-        Some(target)
+        let (col, row) = (self.cursor.pos.col, self.cursor.pos.row);
+        let pos = match direction {
+            Direction::Down => self.board.min_wrap_col(col),
+            Direction::Up => self.board.max_wrap_col(col),
+            Direction::Left => self.board.max_wrap_row(row),
+            Direction::Right => self.board.min_wrap_row(row),
+        };
+        Cursor { pos, direction }
     }
 
     fn password(&self) -> usize {
@@ -243,53 +396,58 @@ impl Trip {
 }
 
 struct Commands {
-    state: VecDeque<char>,
+    state: Vec<Action>,
 }
 
 impl Commands {
-    fn new(input: &Vec<&str>) -> Self {
-        let raw = input[input.len() - 1];
-        let state = raw.chars().collect();
-        Self { state }
-    }
-
-    fn pop_command(&mut self) -> Option<Action> {
-        match self.state.pop_front() {
-            None => None,
-            Some(x) if x.is_alphabetic() => Some(Action::Turn(x.into())),
-            Some(x) => {
-                let mut res = String::from(x);
-                while let Some(y) = self.state.pop_front() {
-                    if y.is_numeric() {
-                        res.push(y);
-                    } else {
-                        self.state.push_front(y);
-                        break;
-                    }
-                }
-                Some(Action::Go(
-                    res.parse()
-                        .unwrap_or_else(|_| panic!("can't parse {}", res)),
-                ))
+    fn new(input: &[&str]) -> Result<Self, ParseError> {
+        let i = input.len() - 1;
+        let mut scanner = Scanner::new(Day22::DAY, i, input[i]);
+        let mut state = Vec::new();
+        while !scanner.is_empty() {
+            if scanner.rest().starts_with(|c: char| c.is_ascii_digit()) {
+                state.push(Action::Go(scanner.number()?));
+            } else {
+                let direction = scanner.char_map("a number, R or L", Direction::from_turn)?;
+                state.push(Action::Turn(direction));
             }
         }
+        Ok(Self { state })
     }
 }
 
-pub fn solve(input: &Vec<&str>) -> usize {
-    let mut trip = Trip::new(input);
-    let mut commands = Commands::new(input);
+pub fn solve(input: &[&str], mode: Mode) -> Result<usize> {
+    Notes::new(input)?.password(mode)
+}
+
+pub struct Day22;
+
+impl Solution for Day22 {
+    const YEAR: u32 = 2022;
+    const DAY: u32 = 22;
+
+    type Input = Notes;
+    type Part1 = usize;
+    type Part2 = usize;
 
-    while let Some(command) = commands.pop_command() {
-        trip.action(&command);
+    fn parse(input: &str) -> Result<Self::Input> {
+        let input: Vec<&str> = input.lines().collect();
+        Ok(Notes::new(&input)?)
     }
 
-    trip.password()
+    fn part1(notes: &Self::Input) -> Result<usize> {
+        notes.password(Mode::Flat)
+    }
+
+    fn part2(notes: &Self::Input) -> Result<usize> {
+        notes.password(Mode::Cube)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::util;
 
     #[test]
     fn test_solve() {
@@ -309,13 +467,109 @@ mod tests {
             "",
             "10R5L5R10L4R5L5",
         ];
-        assert_eq!(solve(&input), 6032);
+        assert_eq!(solve(&input, Mode::Flat).unwrap(), 6032);
+        assert_eq!(solve(&input, Mode::Cube).unwrap(), 5031);
     }
 
-    // #[test]
-    // fn test_with_real_data() {
-        // let input = util::read_real_data("day22");
-        // let input = input.iter().map(|line| line.as_str()).collect();
-        // assert_eq!(solve(&input), 123);
-    // }
+    #[test]
+    fn test_parse_error() {
+        let input = vec!["  ..#", "  .x.", "", "10R5"];
+        let error = solve(&input, Mode::Flat).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 22: line 2, column 4: expected ' ', '.' or '#', found \"x.\""
+        );
+
+        let input = vec!["  ..#", "  ...", "", "10R5U2"];
+        let error = solve(&input, Mode::Flat).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 22: line 4, column 5: expected a number, R or L, found \"U2\""
+        );
+
+        let input = vec!["  ..#", "  ...", "10R5"];
+        let error = solve(&input, Mode::Flat).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 22: line 2, column 1: expected the map, a blank line and the path, found \"  ...\""
+        );
+    }
+
+    #[test]
+    fn test_every_net_folds() {
+        let nets: [&[&str]; 11] = [
+            &["#...", "####", "#..."],
+            &["#...", "####", ".#.."],
+            &["#...", "####", "..#."],
+            &["#...", "####", "...#"],
+            &[".#..", "####", ".#.."],
+            &[".#..", "####", "..#."],
+            &["##..", ".###", ".#.."],
+            &["##..", ".###", "..#."],
+            &["##..", ".###", "...#"],
+            &["##..", ".##.", "..##"],
+            &["###..", "..###"],
+        ];
+        let size = 3;
+        for net in nets {
+            let mut input: Vec<String> = net
+                .iter()
+                .flat_map(|row| {
+                    let line: String = row
+                        .chars()
+                        .flat_map(|c| std::iter::repeat_n(if c == '#' { '.' } else { ' ' }, size))
+                        .collect();
+                    std::iter::repeat_n(line, size)
+                })
+                .collect();
+            input.extend(["".to_owned(), "1".to_owned()]);
+            let input: Vec<&str> = input.iter().map(|line| line.as_str()).collect();
+            let notes = Notes::new(&input).unwrap();
+            let mut trip = Trip::new(&notes, Mode::Cube).unwrap();
+
+            // going straight around the cube leads back to where it started
+            let tiles: Vec<Pos> = trip
+                .board
+                .grid
+                .iter()
+                .filter(|(_, c)| **c == '.')
+                .map(|(pos, _)| pos)
+                .collect();
+            for pos in tiles {
+                for direction in Direction::ALL {
+                    trip.cursor = Cursor { pos, direction };
+                    trip.action(&Action::Go(4 * size));
+                    assert_eq!(trip.cursor.pos, pos, "{:?} {:?}", net, direction);
+                    assert_eq!(trip.cursor.direction, direction);
+                }
+            }
+        }
+
+        let input = ["....", "....", "", "1"];
+        let notes = Notes::new(&input).unwrap();
+        let error = Trip::new(&notes, Mode::Cube).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "day 22: the map doesn't fold into a cube"
+        );
+
+        // enough tiles for faces of 2, but none of them starts in the top row
+        let mut input = vec![" ."];
+        input.extend(["...."; 5]);
+        input.extend(["...", "", "1"]);
+        let notes = Notes::new(&input).unwrap();
+        let error = Trip::new(&notes, Mode::Cube).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "day 22: the map doesn't fold into a cube"
+        );
+    }
+
+    #[test]
+    fn test_with_real_data() {
+        let input = util::read_real_data("day22");
+        let input: Vec<&str> = input.iter().map(|line| line.as_str()).collect();
+        assert_eq!(solve(&input, Mode::Flat).unwrap(), 133174);
+        assert_eq!(solve(&input, Mode::Cube).unwrap(), 15410);
+    }
 }
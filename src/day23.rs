@@ -1,23 +1,12 @@
-use std::{
-    cmp::{max, min},
-    collections::{HashMap, HashSet, VecDeque},
-};
-
-type Position = (i32, i32);
-
-fn adjacent((col, row): &(i32, i32)) -> HashSet<Position> {
-    HashSet::from([
-        (*col - 1, *row - 1),
-        (*col, *row - 1),
-        (*col + 1, *row - 1),
-        (*col + 1, *row),
-        (*col + 1, *row + 1),
-        (*col, *row + 1),
-        (*col - 1, *row + 1),
-        (*col - 1, *row),
-    ])
-}
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::Result;
 
+use crate::error::{ParseError, Scanner};
+use crate::grid::{Pos, SparseGrid};
+use crate::solution::Solution;
+
+#[derive(Clone)]
 enum Direction {
     North,
     East,
@@ -26,39 +15,47 @@ enum Direction {
 }
 
 impl Direction {
-    fn go(&self, (col, row): &Position) -> Position {
+    fn offset(&self) -> Pos {
         match self {
-            Direction::North => (*col, *row - 1),
-            Direction::East => (*col + 1, *row),
-            Direction::South => (*col, *row + 1),
-            Direction::West => (*col - 1, *row),
+            Direction::North => Pos::UP,
+            Direction::East => Pos::RIGHT,
+            Direction::South => Pos::DOWN,
+            Direction::West => Pos::LEFT,
         }
     }
 
-    fn check_points(&self, (col, row): &Position) -> HashSet<Position> {
-        let result = match self {
-            Direction::North => [(*col - 1, *row - 1), (*col, *row - 1), (*col + 1, *row - 1)],
-            Direction::East => [(*col + 1, *row - 1), (*col + 1, *row), (col + 1, row + 1)],
-            Direction::South => [(*col - 1, *row + 1), (*col, *row + 1), (*col + 1, *row + 1)],
-            Direction::West => [(*col - 1, *row - 1), (*col - 1, *row), (*col - 1, *row + 1)],
-        };
-        HashSet::from(result)
+    fn check_points(&self, pos: Pos) -> [Pos; 3] {
+        let ahead = pos + self.offset();
+        match self {
+            Direction::North | Direction::South => [ahead + Pos::LEFT, ahead, ahead + Pos::RIGHT],
+            Direction::East | Direction::West => [ahead + Pos::UP, ahead, ahead + Pos::DOWN],
+        }
     }
 }
 
-struct State {
-    elves: HashSet<Position>,
+/// Where the elves are and which direction they look in first.
+#[derive(Clone)]
+pub struct State {
+    elves: SparseGrid<()>,
     directions: VecDeque<Direction>,
 }
 
 impl State {
-    fn new(input: &[&str]) -> Self {
-        let mut elves = HashSet::new();
+    fn new(input: &[&str]) -> Result<Self, ParseError> {
+        let mut elves = SparseGrid::new();
         for (row_index, row) in input.iter().enumerate() {
-            for (col, val) in row.chars().enumerate() {
-                if val == '#' {
-                    elves.insert((col as i32, row_index as i32));
+            let mut scanner = Scanner::new(Day23::DAY, row_index, row);
+            let mut col = 0;
+            while !scanner.is_empty() {
+                let is_elf = scanner.char_map("'.' or '#'", |c| match c {
+                    '#' => Some(true),
+                    '.' => Some(false),
+                    _ => None,
+                })?;
+                if is_elf {
+                    elves.insert(Pos::new(col, row_index as i32), ());
                 }
+                col += 1;
             }
         }
         let directions = VecDeque::from([
@@ -67,48 +64,33 @@ impl State {
             Direction::West,
             Direction::East,
         ]);
-        Self { elves, directions }
+        Ok(Self { elves, directions })
     }
 
-    fn get_moving_elves(&self) -> HashSet<Position> {
-        let mut moving_elves = HashSet::new();
-        for elf @ (col, row) in self.elves.iter() {
-            let adjacent = adjacent(elf);
-            let mut occupied = adjacent.intersection(&self.elves);
-            if occupied.next().is_some() {
-                moving_elves.insert((*col, *row));
-            }
-        }
-        moving_elves
+    fn get_moving_elves(&self) -> Vec<Pos> {
+        self.elves
+            .positions()
+            .filter(|elf| self.elves.neighbours8(*elf).next().is_some())
+            .collect()
     }
 
-    fn group_target_moves(
-        &self,
-        moving_elves: HashSet<Position>,
-    ) -> HashMap<Position, Vec<Position>> {
-        let mut result = HashMap::new();
+    fn group_target_moves(&self, moving_elves: Vec<Pos>) -> HashMap<Pos, Vec<Pos>> {
+        let mut result: HashMap<Pos, Vec<Pos>> = HashMap::new();
         for elf in moving_elves {
             for direction in self.directions.iter() {
-                let check_points = direction.check_points(&elf);
-                if check_points.intersection(&self.elves).next().is_some() {
+                let check_points = direction.check_points(elf);
+                if check_points.iter().any(|pos| self.elves.contains(*pos)) {
                     continue;
                 }
-                let destination = direction.go(&elf);
-                match result.get_mut(&destination) {
-                    None => {
-                        result.insert(destination, Vec::from([elf]));
-                    }
-                    Some(elves) => {
-                        elves.push(elf);
-                    }
-                };
+                let destination = elf + direction.offset();
+                result.entry(destination).or_default().push(elf);
                 break;
             }
         }
         result
     }
 
-    fn move_elves(&mut self, target_to_elves: HashMap<Position, Vec<Position>>) -> u32 {
+    fn move_elves(&mut self, target_to_elves: HashMap<Pos, Vec<Pos>>) -> u32 {
         let mut result = 0;
         for (target, elves) in target_to_elves.iter() {
             if elves.len() > 1 {
@@ -116,8 +98,8 @@ impl State {
             }
 
             let elf = elves[0];
-            self.elves.remove(&elf);
-            self.elves.insert(*target);
+            self.elves.remove(elf);
+            self.elves.insert(*target, ());
             result += 1;
         }
         result
@@ -137,32 +119,31 @@ impl State {
     }
 
     fn count_empty_ground(&self) -> i32 {
-        let mut north = i32::MAX;
-        let mut west = i32::MAX;
-        let mut east = i32::MIN;
-        let mut south = i32::MIN;
-
-        for (col, row) in self.elves.iter() {
-            north = min(north, *row);
-            west = min(west, *col);
-            east = max(east, *col);
-            south = max(south, *row);
-        }
-
-        (south - north + 1) * (east - west + 1) - self.elves.len() as i32
+        let Some((min, max)) = self.elves.bounds() else {
+            return 0;
+        };
+        (max.row - min.row + 1) * (max.col - min.col + 1) - self.elves.len() as i32
     }
 }
 
-pub fn solve(input: &[&str]) -> i32 {
-    let mut state = State::new(input);
+pub fn solve(input: &[&str]) -> Result<i32> {
+    Ok(empty_ground(&State::new(input)?))
+}
+
+fn empty_ground(state: &State) -> i32 {
+    let mut state = state.clone();
     for _ in 0..10 {
         state.step();
     }
     state.count_empty_ground()
 }
 
-pub fn part2(input: &[&str]) -> u32 {
-    let mut state = State::new(input);
+pub fn part2(input: &[&str]) -> Result<u32> {
+    Ok(last_round(&State::new(input)?))
+}
+
+fn last_round(state: &State) -> u32 {
+    let mut state = state.clone();
     let mut result = 1;
     while state.step() != 0 {
         result += 1;
@@ -170,6 +151,30 @@ pub fn part2(input: &[&str]) -> u32 {
     result
 }
 
+pub struct Day23;
+
+impl Solution for Day23 {
+    const YEAR: u32 = 2022;
+    const DAY: u32 = 23;
+
+    type Input = State;
+    type Part1 = i32;
+    type Part2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let input: Vec<&str> = input.lines().collect();
+        Ok(State::new(&input)?)
+    }
+
+    fn part1(state: &Self::Input) -> Result<i32> {
+        Ok(empty_ground(state))
+    }
+
+    fn part2(state: &Self::Input) -> Result<u32> {
+        Ok(last_round(state))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -180,7 +185,7 @@ mod tests {
         let input = [
             "....#..", "..###.#", "#...#.#", ".#...##", "#.###..", "##.#.##", ".#..#..",
         ];
-        assert_eq!(solve(&input), 110);
+        assert_eq!(solve(&input).unwrap(), 110);
     }
 
     #[test]
@@ -188,20 +193,29 @@ mod tests {
         let input = [
             "....#..", "..###.#", "#...#.#", ".#...##", "#.###..", "##.#.##", ".#..#..",
         ];
-        assert_eq!(part2(&input), 20);
+        assert_eq!(part2(&input).unwrap(), 20);
     }
 
     #[test]
     fn test_with_real_data() {
         let input = util::read_real_data("day23");
         let input: Vec<&str> = input.iter().map(|line| line.as_str()).collect();
-        assert_eq!(solve(&input), 3788);
+        assert_eq!(solve(&input).unwrap(), 3788);
     }
 
     #[test]
     fn test_part2_with_real_data() {
         let input = util::read_real_data("day23");
         let input: Vec<&str> = input.iter().map(|line| line.as_str()).collect();
-        assert_eq!(part2(&input), 921);
+        assert_eq!(part2(&input).unwrap(), 921);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let error = solve(&["....#..", "..#O#.#"]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 23: line 2, column 4: expected '.' or '#', found \"O#.#\""
+        );
     }
 }
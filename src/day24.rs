@@ -1,8 +1,13 @@
-use std::{
-    cmp::Ordering,
-    collections::{BinaryHeap, HashSet},
-};
+use std::collections::HashSet;
 
+use anyhow::{anyhow, Result};
+
+use crate::error::{ParseError, Scanner};
+use crate::grid::{Grid, Pos};
+use crate::search;
+use crate::solution::Solution;
+
+#[derive(Clone)]
 struct Blizzard {
     col: i32,
     row: i32,
@@ -10,7 +15,7 @@ struct Blizzard {
 }
 
 impl Blizzard {
-    fn at(&self, width: i32, height: i32, t: i32) -> (i32, i32) {
+    fn at(&self, width: i32, height: i32, t: i32) -> Pos {
         match self.direction {
             '>' => {
                 // (A + B) mod C = (A mod C + B mod C) mod C
@@ -22,7 +27,7 @@ impl Blizzard {
                 if res >= width {
                     res -= c;
                 }
-                (res, self.row)
+                Pos::new(res, self.row)
             }
             '^' => {
                 // (A - B) mod C = (A mod C - B mod C) mod C
@@ -34,7 +39,7 @@ impl Blizzard {
                 if res <= 0 {
                     res += c;
                 }
-                (self.col, res)
+                Pos::new(self.col, res)
             }
             '<' => {
                 // (A - B) mod C = (A mod C - B mod C) mod C
@@ -46,7 +51,7 @@ impl Blizzard {
                 if res <= 0 {
                     res += c;
                 }
-                (res, self.row)
+                Pos::new(res, self.row)
             }
             'v' => {
                 // (A + B) mod C = (A mod C + B mod C) mod C
@@ -58,134 +63,108 @@ impl Blizzard {
                 if res >= height {
                     res -= c;
                 }
-                (self.col, res)
+                Pos::new(self.col, res)
             }
             _ => panic!("incorrect direction {}", self.direction),
         }
     }
 }
 
-struct Graph {
-    start: (i32, i32),
-    end: (i32, i32),
+/// The valley, its entrance and exit, and the blizzards in it at minute 0.
+#[derive(Clone)]
+pub struct Graph {
+    start: Pos,
+    end: Pos,
     width: i32,
     height: i32,
     blizzards: Vec<Blizzard>,
 }
 
-#[derive(PartialEq, Eq)]
-struct Item {
-    position: (i32, i32),
-    t: i32,
-    end: (i32, i32),
-}
-
-impl Item {
-    fn dist_to_end(&self) -> i32 {
-        self.t + (self.end.0 - self.position.0).abs() + (self.end.1 - self.position.1).abs()
-    }
-}
-
-impl Ord for Item {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other.dist_to_end().cmp(&self.dist_to_end())
-    }
-}
-
-impl PartialOrd for Item {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
 impl Graph {
-    fn new(input: &[&str]) -> Self {
-        let start = input[0].chars().position(|val| val == '.').unwrap();
-        let end = input
-            .last()
-            .unwrap()
-            .chars()
-            .position(|val| val == '.')
-            .unwrap();
-
-        let blizzards = Graph::collect_blizzards(input);
-        let height = input.len() as i32;
-
-        Self {
-            start: (start as i32, 0),
-            end: (end as i32, height - 1),
-            width: input[0].len() as i32,
-            height,
-            blizzards,
+    fn new(input: &[&str]) -> Result<Self, ParseError> {
+        if input.len() < 3 {
+            return Err(ParseError::new(
+                Day24::DAY,
+                input.len() + 1,
+                1,
+                "at least 3 rows",
+                "end of input",
+            ));
         }
+        let grid = Grid::parse(Day24::DAY, input, "'#', '.', '<', '>', 'v' or '^'", |c| {
+            "#.<>v^".contains(c).then_some(c)
+        })?;
+        let start = Graph::find_gap(&grid, input, 0)?;
+        let end = Graph::find_gap(&grid, input, grid.height() - 1)?;
+
+        Ok(Self {
+            start,
+            end,
+            width: grid.width() as i32,
+            height: grid.height() as i32,
+            blizzards: Graph::collect_blizzards(&grid),
+        })
     }
 
-    fn collect_blizzards(input: &[&str]) -> Vec<Blizzard> {
-        let mut blizzards = Vec::new();
-        for (row, row_data) in input.iter().enumerate() {
-            for (col, direction) in row_data.chars().enumerate() {
-                if "<>v^".contains(direction) {
-                    blizzards.push(Blizzard {
-                        col: col as i32,
-                        row: row as i32,
-                        direction,
-                    });
-                }
-            }
-        }
-        blizzards
+    fn find_gap(grid: &Grid<char>, input: &[&str], row: usize) -> Result<Pos, ParseError> {
+        let col = grid.row(row).iter().position(|val| *val == '.');
+        let col = col
+            .ok_or_else(|| Scanner::new(Day24::DAY, row, input[row]).error("a gap in the wall"))?;
+        Ok(Pos::new(col as i32, row as i32))
     }
 
-    fn search(&mut self) -> i32 {
-        let mut queue = BinaryHeap::new();
-        self.push(&mut queue, self.start, 0);
-
-        let mut jumps = HashSet::new();
-        let mut time_to_blizzards = Vec::new();
-
-        while !queue.is_empty() {
-            let (u, t) = self.pop(&mut queue);
-            let mut adjacent = self.adjacent(u);
-            let blizzards = self.cached_blizzards(&mut time_to_blizzards, t);
-            adjacent.retain(|position| !blizzards.contains(position));
-            for v in adjacent.iter() {
-                if *v == self.end {
-                    return t;
-                }
-                if jumps.insert((*v, t + 1)) {
-                    self.push(&mut queue, *v, t + 1);
-                }
-            }
-        }
-        unreachable!()
+    fn collect_blizzards(grid: &Grid<char>) -> Vec<Blizzard> {
+        grid.iter()
+            .filter(|(_, direction)| "<>v^".contains(**direction))
+            .map(|(pos, direction)| Blizzard {
+                col: pos.col,
+                row: pos.row,
+                direction: *direction,
+            })
+            .collect()
     }
 
-    fn pop(&self, queue: &mut BinaryHeap<Item>) -> ((i32, i32), i32) {
-        let result = queue.pop().unwrap();
-        (result.position, result.t)
+    /// Minutes to get from start to end when setting off at minute `t0`.
+    ///
+    /// States keep the minute modulo the blizzards' period, so the search runs out of states
+    /// instead of waiting forever when there's no way through.
+    fn search(&self, t0: i32, cache: &mut Vec<HashSet<Pos>>) -> Result<i32> {
+        let period = self.period();
+        let successors = |&(u, t): &(Pos, i32)| {
+            let t = (t + 1) % period;
+            let blizzards = self.cached_blizzards(cache, t);
+            self.adjacent(u)
+                .into_iter()
+                .filter(|v| !blizzards.contains(v))
+                .map(|v| ((v, t), 1))
+                .collect::<Vec<_>>()
+        };
+        let heuristic = |(u, _): &(Pos, i32)| u.manhattan(self.end);
+        let start = (self.start, t0 % period);
+        let path = search::astar([start], successors, heuristic, |(u, _)| *u == self.end);
+        let path = path.ok_or_else(|| anyhow!("day 24: no way through the blizzards"))?;
+        Ok(path.cost as i32)
     }
 
-    fn push(&self, queue: &mut BinaryHeap<Item>, position: (i32, i32), t: i32) {
-        queue.push(Item {
-            position,
-            t,
-            end: self.end,
-        });
+    // minutes until every blizzard is back where it started
+    fn period(&self) -> i32 {
+        let (a, b) = ((self.width - 2).max(1), (self.height - 2).max(1));
+        a / gcd(a, b) * b
     }
 
     fn cached_blizzards<'a>(
-        &'a self,
-        time_to_blizzards: &'a mut Vec<HashSet<(i32, i32)>>,
+        &self,
+        time_to_blizzards: &'a mut Vec<HashSet<Pos>>,
         t: i32,
-    ) -> &'a HashSet<(i32, i32)> {
-        if time_to_blizzards.len() == t as usize {
-            let blizzards = self.blizzards_at(t);
+    ) -> &'a HashSet<Pos> {
+        while time_to_blizzards.len() <= t as usize {
+            let blizzards = self.blizzards_at(time_to_blizzards.len() as i32);
             time_to_blizzards.push(blizzards);
         }
         &time_to_blizzards[t as usize]
     }
 
-    fn blizzards_at(&self, t: i32) -> HashSet<(i32, i32)> {
+    fn blizzards_at(&self, t: i32) -> HashSet<Pos> {
         let mut result = HashSet::new();
         for b in self.blizzards.iter() {
             let pos = b.at(self.width, self.height, t);
@@ -194,48 +173,79 @@ impl Graph {
         result
     }
 
-    fn adjacent(&self, (col, row): (i32, i32)) -> Vec<(i32, i32)> {
-        let adjacent = [
-            (col - 1, row),
-            (col, row),
-            (col + 1, row),
-            (col, row - 1),
-            (col, row + 1),
-        ];
-        let result = adjacent
-            .iter()
-            .filter(|(c, r)| !self.is_perimiter((*c, *r)))
-            .copied()
-            .collect();
-        result
+    fn adjacent(&self, pos: Pos) -> Vec<Pos> {
+        let mut adjacent = pos.neighbours4().to_vec();
+        adjacent.push(pos);
+        adjacent.retain(|v| !self.is_perimiter(*v));
+        adjacent
     }
 
-    fn is_perimiter(&self, vertice: (i32, i32)) -> bool {
-        let (col, row) = (vertice.0, vertice.1);
+    fn is_perimiter(&self, vertice: Pos) -> bool {
         if vertice == self.start || vertice == self.end {
             return false;
         }
-        col < 1 || col >= self.width - 1 || row < 1 || row >= self.height - 1
+        vertice.col < 1
+            || vertice.col >= self.width - 1
+            || vertice.row < 1
+            || vertice.row >= self.height - 1
     }
 }
 
-pub fn solve(input: &[&str]) -> i32 {
-    let mut graph = Graph::new(input);
-    graph.search()
+fn gcd(a: i32, b: i32) -> i32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
 }
 
-pub fn part2(input: &[&str]) -> i32 {
-    let mut graph = Graph::new(input);
-    let forward = graph.search();
+pub fn solve(input: &[&str]) -> Result<i32> {
+    Graph::new(input)?.search(0, &mut Vec::new())
+}
+
+pub fn part2(input: &[&str]) -> Result<i32> {
+    there_and_back_again(&Graph::new(input)?)
+}
+
+/// Minutes to go to the end, back to the start and to the end again. Each leg sets off at the
+/// minute the previous one arrived, with the blizzards where they are by then.
+fn there_and_back_again(graph: &Graph) -> Result<i32> {
+    let mut graph = graph.clone();
+    let mut cache = Vec::new();
+    let forward = graph.search(0, &mut cache)?;
 
     let (start, end) = (graph.start, graph.end);
     (graph.start, graph.end) = (end, start);
-    let backward = graph.search();
+    let backward = graph.search(forward, &mut cache)?;
 
     (graph.start, graph.end) = (start, end);
-    let again = graph.search();
+    let again = graph.search(forward + backward, &mut cache)?;
 
-    forward + backward + again + 1
+    Ok(forward + backward + again)
+}
+
+pub struct Day24;
+
+impl Solution for Day24 {
+    const YEAR: u32 = 2022;
+    const DAY: u32 = 24;
+
+    type Input = Graph;
+    type Part1 = i32;
+    type Part2 = i32;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let input: Vec<&str> = input.lines().collect();
+        Ok(Graph::new(&input)?)
+    }
+
+    fn part1(graph: &Self::Input) -> Result<i32> {
+        graph.search(0, &mut Vec::new())
+    }
+
+    fn part2(graph: &Self::Input) -> Result<i32> {
+        there_and_back_again(graph)
+    }
 }
 
 #[cfg(test)]
@@ -249,7 +259,7 @@ mod tests {
             "#.######", "#>>.<^<#", "#.<..<<#", "#>v.><>#", "#<^v^^>#", "######.#",
         ];
 
-        assert_eq!(solve(&input), 18);
+        assert_eq!(solve(&input).unwrap(), 18);
     }
 
     #[test]
@@ -258,21 +268,62 @@ mod tests {
             "#.######", "#>>.<^<#", "#.<..<<#", "#>v.><>#", "#<^v^^>#", "######.#",
         ];
 
-        assert_eq!(part2(&input), 54);
+        assert_eq!(part2(&input).unwrap(), 54);
+    }
+
+    #[test]
+    fn test_part2_legs() {
+        let input = [
+            "#.######", "#>>.<^<#", "#.<..<<#", "#>v.><>#", "#<^v^^>#", "######.#",
+        ];
+        let graph = Graph::new(&input).unwrap();
+        let mut back = graph.clone();
+        (back.start, back.end) = (graph.end, graph.start);
+
+        let mut cache = Vec::new();
+        assert_eq!(graph.search(0, &mut cache).unwrap(), 18);
+        assert_eq!(back.search(18, &mut cache).unwrap(), 23);
+        assert_eq!(graph.search(18 + 23, &mut cache).unwrap(), 13);
+    }
+
+    #[test]
+    fn test_no_way_through() {
+        let input = ["#.#", "#v#", "#v#", "#.#"];
+
+        let error = solve(&input).unwrap_err();
+        assert_eq!(error.to_string(), "day 24: no way through the blizzards");
     }
 
     #[test]
     fn test_with_real_data() {
         let input = util::read_real_data("day24");
         let input: Vec<&str> = input.iter().map(|line| line.as_str()).collect();
-        assert_eq!(solve(&input), 232);
+        assert_eq!(solve(&input).unwrap(), 232);
     }
     
     #[test]
     fn test_part2_with_real_data() {
         let input = util::read_real_data("day24");
         let input: Vec<&str> = input.iter().map(|line| line.as_str()).collect();
-        assert_eq!(part2(&input), 700);
+        assert_eq!(part2(&input).unwrap(), 715);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let input = ["#.####", "#>x.<#", "####.#"];
+        let error = Graph::new(&input).err().unwrap();
+        let expected = "'#', '.', '<', '>', 'v' or '^'";
+        assert_eq!(error, ParseError::new(24, 2, 3, expected, "x.<#"));
+
+        let input = ["#.####", "#>..<", "####.#"];
+        let error = Graph::new(&input).err().unwrap();
+        let expected = "'#', '.', '<', '>', 'v' or '^'";
+        assert_eq!(error, ParseError::new(24, 2, 6, expected, "end of line"));
+
+        let input = ["#.####", "#>..<#", "######"];
+        let error = Graph::new(&input).err().unwrap();
+        let expected = "a gap in the wall";
+        assert_eq!(error, ParseError::new(24, 3, 1, expected, "######"));
     }
 
     #[test]
@@ -282,7 +333,7 @@ mod tests {
             row: 2,
             direction: '>',
         };
-        assert_eq!(blizzard.at(7, 7, 5), (1, 2));
+        assert_eq!(blizzard.at(7, 7, 5), Pos::new(1, 2));
     }
 
     #[test]
@@ -292,7 +343,7 @@ mod tests {
             row: 4,
             direction: 'v',
         };
-        assert_eq!(blizzard.at(7, 7, 10), (4, 4));
+        assert_eq!(blizzard.at(7, 7, 10), Pos::new(4, 4));
     }
 
     #[test]
@@ -302,9 +353,9 @@ mod tests {
             row: 3,
             direction: '<',
         };
-        assert_eq!(blizzard.at(7, 7, 4), (4, 3));
-        assert_eq!(blizzard.at(7, 7, 10), (3, 3));
-        assert_eq!(blizzard.at(7, 7, 14), (4, 3));
+        assert_eq!(blizzard.at(7, 7, 4), Pos::new(4, 3));
+        assert_eq!(blizzard.at(7, 7, 10), Pos::new(3, 3));
+        assert_eq!(blizzard.at(7, 7, 14), Pos::new(4, 3));
     }
 
     #[test]
@@ -314,6 +365,6 @@ mod tests {
             row: 1,
             direction: '^',
         };
-        assert_eq!(blizzard.at(8, 6, 9), (5, 4));
+        assert_eq!(blizzard.at(8, 6, 9), Pos::new(5, 4));
     }
 }
@@ -1,17 +1,23 @@
-fn snafu_to_decimal(snafu: &str) -> i64 {
+use anyhow::Result;
+
+use crate::error::{ParseError, Scanner};
+use crate::solution::{Solution, Unsolved};
+
+fn snafu_to_decimal(i: usize, snafu: &str) -> Result<i64, ParseError> {
+    let mut scanner = Scanner::new(Day25::DAY, i, snafu);
     let mut res = 0;
-    for (i, v) in snafu.chars().rev().enumerate() {
-        let v = match v {
-            '2' => 2,
-            '1' => 1,
-            '0' => 0,
-            '-' => -1,
-            '=' => -2,
-            _ => panic!("unknown token {}", v),
-        };
-        res += 5_i64.pow(i as u32) * v;
+    while !scanner.is_empty() {
+        let v = scanner.char_map("2, 1, 0, - or =", |v| match v {
+            '2' => Some(2),
+            '1' => Some(1),
+            '0' => Some(0),
+            '-' => Some(-1),
+            '=' => Some(-2),
+            _ => None,
+        })?;
+        res = res * 5 + v;
     }
-    res
+    Ok(res)
 }
 
 fn decimal_to_snafu(mut decimal: i64) -> String {
@@ -33,9 +39,40 @@ fn decimal_to_snafu(mut decimal: i64) -> String {
     res.chars().rev().collect()
 }
 
-pub fn solve(input: &[&str]) -> String {
-    let sum = input.iter().map(|line| snafu_to_decimal(line)).sum();
-    decimal_to_snafu(sum)
+fn parse(input: &[&str]) -> Result<Vec<i64>, ParseError> {
+    let mut result = Vec::new();
+    for (i, line) in input.iter().enumerate() {
+        result.push(snafu_to_decimal(i, line)?);
+    }
+    Ok(result)
+}
+
+pub fn solve(input: &[&str]) -> Result<String> {
+    Ok(decimal_to_snafu(parse(input)?.iter().sum()))
+}
+
+pub struct Day25;
+
+impl Solution for Day25 {
+    const YEAR: u32 = 2022;
+    const DAY: u32 = 25;
+
+    type Input = Vec<i64>;
+    type Part1 = String;
+    type Part2 = Unsolved;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let input: Vec<&str> = input.lines().collect();
+        Ok(parse(&input)?)
+    }
+
+    fn part1(numbers: &Self::Input) -> Result<String> {
+        Ok(decimal_to_snafu(numbers.iter().sum()))
+    }
+
+    fn part2(_: &Self::Input) -> Result<Unsolved> {
+        Ok(Unsolved)
+    }
 }
 
 #[cfg(test)]
@@ -47,7 +84,7 @@ mod tests {
     fn test_with_real_data(){
         let input = util::read_real_data("day25");
         let input: Vec<&str> = input.iter().map(|line| line.as_str()).collect();
-        assert_eq!(solve(&input), "asd");
+        assert_eq!(solve(&input).unwrap(), "2-=2==00-0==2=022=10");
     }
 
     #[test]
@@ -56,24 +93,33 @@ mod tests {
             "1=-0-2", "12111", "2=0=", "21", "2=01", "111", "20012", "112", "1=-1=", "1-12", "12",
             "1=", "122",
         ];
-        assert_eq!(solve(&input), "2=-1=0");
+        assert_eq!(solve(&input).unwrap(), "2=-1=0");
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let error = solve(&["1=-0-2", "12131"]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 25: line 2, column 4: expected 2, 1, 0, - or =, found \"31\""
+        );
     }
 
     #[test]
     fn test_snafu_to_decimal() {
-        assert_eq!(snafu_to_decimal("1=-0-2"), 1747);
-        assert_eq!(snafu_to_decimal("12111"), 906);
-        assert_eq!(snafu_to_decimal("2=0="), 198);
-        assert_eq!(snafu_to_decimal("21"), 11);
-        assert_eq!(snafu_to_decimal("2=01"), 201);
-        assert_eq!(snafu_to_decimal("111"), 31);
-        assert_eq!(snafu_to_decimal("20012"), 1257);
-        assert_eq!(snafu_to_decimal("112"), 32);
-        assert_eq!(snafu_to_decimal("1=-1="), 353);
-        assert_eq!(snafu_to_decimal("1-12"), 107);
-        assert_eq!(snafu_to_decimal("12"), 7);
-        assert_eq!(snafu_to_decimal("1="), 3);
-        assert_eq!(snafu_to_decimal("122"), 37);
+        assert_eq!(snafu_to_decimal(0, "1=-0-2").unwrap(), 1747);
+        assert_eq!(snafu_to_decimal(0, "12111").unwrap(), 906);
+        assert_eq!(snafu_to_decimal(0, "2=0=").unwrap(), 198);
+        assert_eq!(snafu_to_decimal(0, "21").unwrap(), 11);
+        assert_eq!(snafu_to_decimal(0, "2=01").unwrap(), 201);
+        assert_eq!(snafu_to_decimal(0, "111").unwrap(), 31);
+        assert_eq!(snafu_to_decimal(0, "20012").unwrap(), 1257);
+        assert_eq!(snafu_to_decimal(0, "112").unwrap(), 32);
+        assert_eq!(snafu_to_decimal(0, "1=-1=").unwrap(), 353);
+        assert_eq!(snafu_to_decimal(0, "1-12").unwrap(), 107);
+        assert_eq!(snafu_to_decimal(0, "12").unwrap(), 7);
+        assert_eq!(snafu_to_decimal(0, "1=").unwrap(), 3);
+        assert_eq!(snafu_to_decimal(0, "122").unwrap(), 37);
     }
 
     #[test]
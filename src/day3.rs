@@ -1,15 +1,46 @@
 use std::collections::HashSet;
 
-pub fn solve<S: AsRef<str>>(input: &[S]) -> usize {
-    input
-        .iter()
-        .map(|items| items.as_ref())
-        .map(find_item)
-        .map(item_to_priority)
-        .sum()
+use anyhow::Result;
+
+use crate::error::{ParseError, Scanner};
+use crate::solution::Solution;
+
+pub fn solve<S: AsRef<str>>(input: &[S]) -> Result<usize> {
+    Ok(priorities(&parse(input)?)?)
+}
+
+// rucksacks whose items are all letters
+fn parse<S: AsRef<str>>(input: &[S]) -> Result<Vec<String>, ParseError> {
+    let mut result = Vec::new();
+    for (i, line) in input.iter().enumerate() {
+        check_items(i, line.as_ref())?;
+        result.push(line.as_ref().to_owned());
+    }
+    Ok(result)
+}
+
+fn priorities(rucksacks: &[String]) -> Result<usize, ParseError> {
+    let mut result = 0;
+    for (i, line) in rucksacks.iter().enumerate() {
+        let item = find_item(line).ok_or_else(|| {
+            ParseError::new(Day3::DAY, i + 1, 1, "an item in both compartments", line)
+        })?;
+        result += item_to_priority(item);
+    }
+    Ok(result)
 }
 
-fn find_item(line: &str) -> char {
+fn check_items(i: usize, line: &str) -> Result<(), ParseError> {
+    let mut scanner = Scanner::new(Day3::DAY, i, line);
+    while !scanner.is_empty() {
+        scanner.char_map("an item from a-z or A-Z", |c| {
+            Some(c).filter(char::is_ascii_alphabetic)
+        })?;
+    }
+    Ok(())
+}
+
+fn find_item(line: &str) -> Option<char> {
     let size = line.len();
     let middle = size / 2;
     let left = &line[0..middle];
@@ -19,10 +50,7 @@ fn find_item(line: &str) -> char {
     let right: HashSet<char> = right.chars().collect();
 
     let mut intersection = left.intersection(&right);
-    let result = intersection
-        .next()
-        .unwrap_or_else(|| panic!("no intersection in {}", line));
-    *result
+    intersection.next().copied()
 }
 
 const ALPHABET: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
@@ -33,21 +61,29 @@ fn item_to_priority(item: char) -> usize {
         .unwrap_or_else(|| panic!("can't find char {}", item))
 }
 
-pub fn solve_part2<S: AsRef<str>>(input: &Vec<S>) -> usize {
+pub fn solve_part2<S: AsRef<str>>(input: &[S]) -> Result<usize> {
+    Ok(badges(&parse(input)?)?)
+}
+
+fn badges(rucksacks: &[String]) -> Result<usize, ParseError> {
     let mut result = 0;
-    for i in 0..input.len() / 3 {
+    for i in 0..rucksacks.len() / 3 {
         let badge = badge(&[
-            input[3 * i].as_ref(),
-            input[3 * i + 1].as_ref(),
-            input[3 * i + 2].as_ref(),
-        ]);
+            &rucksacks[3 * i],
+            &rucksacks[3 * i + 1],
+            &rucksacks[3 * i + 2],
+        ])
+        .ok_or_else(|| {
+            let line = &rucksacks[3 * i];
+            ParseError::new(Day3::DAY, 3 * i + 1, 1, "one badge in group", line)
+        })?;
         let score = item_to_priority(badge);
         result += score;
     }
-    result
+    Ok(result)
 }
 
-fn badge(group: &[&str; 3]) -> char {
+fn badge(group: &[&str; 3]) -> Option<char> {
     let mut result: HashSet<char> = group[0].chars().collect();
     for item in group.iter().skip(1) {
         let distint: HashSet<char> = item.chars().collect();
@@ -55,11 +91,34 @@ fn badge(group: &[&str; 3]) -> char {
         result = intersection.iter().copied().copied().collect();
     }
 
-    assert!(result.len() == 1, "too long intersection: {:?}", result);
-    result
-        .into_iter()
-        .next()
-        .unwrap_or_else(|| panic!("expect to have badge in group {:?}", group))
+    if result.len() != 1 {
+        return None;
+    }
+    result.into_iter().next()
+}
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    const YEAR: u32 = 2022;
+    const DAY: u32 = 3;
+
+    type Input = Vec<String>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let input: Vec<&str> = input.lines().collect();
+        Ok(parse(&input)?)
+    }
+
+    fn part1(rucksacks: &Self::Input) -> Result<usize> {
+        Ok(priorities(rucksacks)?)
+    }
+
+    fn part2(rucksacks: &Self::Input) -> Result<usize> {
+        Ok(badges(rucksacks)?)
+    }
 }
 
 #[cfg(test)]
@@ -70,7 +129,7 @@ mod tests {
     #[test]
     fn test_find_item() {
         let actual = find_item("vJrwpWtwJgWrhcsFMMfFFhFp");
-        assert_eq!(actual, 'p');
+        assert_eq!(actual, Some('p'));
     }
 
     #[test]
@@ -89,13 +148,13 @@ mod tests {
             "ttgJtRGJQctTZtZT",
             "CrZsJsPPZsGzwwsLwLmpwMDw",
         ];
-        assert_eq!(solve(&input), 157);
+        assert_eq!(solve(&input).unwrap(), 157);
     }
 
     #[test]
     fn test_solve_with_real_data() {
         let input = util::read_real_data("day3");
-        let actual = solve(&input);
+        let actual = solve(&input).unwrap();
         assert_eq!(actual, 7811);
     }
 
@@ -106,13 +165,22 @@ mod tests {
             "jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL",
             "PmmdzqPrVvPwwTWBwg",
         ];
-        assert_eq!(badge(&input), 'r');
+        assert_eq!(badge(&input), Some('r'));
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let error = solve(&["vJrwpWtwJgWrhcsFMMfFFhFp", "jqHRNq-jqzjG"]).unwrap_err();
+        assert_eq!(
+            error.downcast::<ParseError>().unwrap(),
+            ParseError::new(3, 2, 7, "an item from a-z or A-Z", "-jqzjG")
+        );
     }
 
     #[test]
     fn test_part2_with_real_data() {
         let input = util::read_real_data("day3");
-        let actual = solve_part2(&input);
+        let actual = solve_part2(&input).unwrap();
         assert_eq!(actual, 2639);
     }
 }
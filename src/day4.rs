@@ -1,3 +1,8 @@
+use anyhow::Result;
+
+use crate::error::{ParseError, Scanner};
+use crate::solution::Solution;
+
 pub fn fully_contains((s1, e1): (u32, u32), (s2, e2): (u32, u32)) -> bool {
     (s1 >= s2 && e1 <= e2) || (s2 >= s1 && e2 <= e1)
 }
@@ -6,31 +11,67 @@ fn has_overlap((s1, e1): (u32, u32), (s2, e2): (u32, u32)) -> bool {
     !((s1 < s2 && e1 < s2) || (e2 < s1 && e2 < e1))
 }
 
-fn check_line(line: &str, check: &dyn Fn((u32, u32), (u32, u32)) -> bool) -> bool {
-    let tokens: Vec<&str> = line.split(',').collect();
-    let (left, right) = (tokens[0], tokens[1]);
-    let s1e1: Vec<&str> = left.split('-').collect();
-    let s2e2: Vec<&str> = right.split('-').collect();
-    let (s1, e1) = (s1e1[0].parse().expect("s1"), s1e1[1].parse().expect("e1"));
-    let (s2, e2) = (s2e2[0].parse().expect("s2"), s2e2[1].parse().expect("e2"));
+pub type Pair = ((u32, u32), (u32, u32));
+
+fn parse_line(i: usize, line: &str) -> Result<Pair, ParseError> {
+    let mut scanner = Scanner::new(Day4::DAY, i, line);
+    let s1 = scanner.number()?;
+    scanner.tag("-")?;
+    let e1 = scanner.number()?;
+    scanner.tag(",")?;
+    let s2 = scanner.number()?;
+    scanner.tag("-")?;
+    let e2 = scanner.number()?;
+    scanner.end()?;
+
+    Ok(((s1, e1), (s2, e2)))
+}
 
-    check((s1, e1), (s2, e2))
+fn parse<S: AsRef<str>>(input: &[S]) -> Result<Vec<Pair>, ParseError> {
+    let mut result = Vec::new();
+    for (i, line) in input.iter().enumerate() {
+        result.push(parse_line(i, line.as_ref())?);
+    }
+    Ok(result)
 }
 
-pub fn solve<S: AsRef<str>>(input: &[S]) -> u32 {
-    total(input, &fully_contains)
+pub fn solve<S: AsRef<str>>(input: &[S]) -> Result<u32> {
+    Ok(total(&parse(input)?, &fully_contains))
 }
 
-fn total<S: AsRef<str>>(input: &[S], check: &dyn Fn((u32, u32), (u32, u32)) -> bool) -> u32 {
-    input
+fn total(pairs: &[Pair], check: &dyn Fn((u32, u32), (u32, u32)) -> bool) -> u32 {
+    pairs
         .iter()
-        .map(|line| line.as_ref())
-        .map(|line| check_line(line, &check))
-        .fold(0, |acc, x| if x { acc + 1 } else { acc })
+        .filter(|(first, second)| check(*first, *second))
+        .count() as u32
 }
 
-pub fn part2<S: AsRef<str>>(input: &[S]) -> u32 {
-    total(input, &has_overlap)
+pub fn part2<S: AsRef<str>>(input: &[S]) -> Result<u32> {
+    Ok(total(&parse(input)?, &has_overlap))
+}
+
+pub struct Day4;
+
+impl Solution for Day4 {
+    const YEAR: u32 = 2022;
+    const DAY: u32 = 4;
+
+    type Input = Vec<Pair>;
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let input: Vec<&str> = input.lines().collect();
+        Ok(parse(&input)?)
+    }
+
+    fn part1(pairs: &Self::Input) -> Result<u32> {
+        Ok(total(pairs, &fully_contains))
+    }
+
+    fn part2(pairs: &Self::Input) -> Result<u32> {
+        Ok(total(pairs, &has_overlap))
+    }
 }
 
 #[cfg(test)]
@@ -59,8 +100,14 @@ mod tests {
     }
 
     #[test]
-    fn test_check_line() {
-        assert!(check_line("6-6,4-6", &fully_contains));
+    fn test_parse_line() {
+        assert_eq!(parse_line(0, "6-6,4-6"), Ok(((6, 6), (4, 6))));
+    }
+
+    #[test]
+    fn test_parse_line_error() {
+        let error = parse_line(4, "6-6;4-6").unwrap_err();
+        assert_eq!(error, ParseError::new(4, 5, 4, "\",\"", ";4-6"));
     }
 
     #[test]
@@ -68,19 +115,19 @@ mod tests {
         let input = vec![
             "2-4,6-8", "2-3,4-5", "5-7,7-9", "2-8,3-7", "6-6,4-6", "2-6,4-8",
         ];
-        assert_eq!(solve(&input), 2);
+        assert_eq!(solve(&input).unwrap(), 2);
     }
 
     #[test]
     fn test_with_real_data() {
         let input = util::read_real_data("day4");
-        assert_eq!(solve(&input), 576);
+        assert_eq!(solve(&input).unwrap(), 576);
     }
 
     #[test]
     fn test_part2_with_real_data() {
         let input = util::read_real_data("day4");
-        assert_eq!(part2(&input), 905);
+        assert_eq!(part2(&input).unwrap(), 905);
     }
 
     #[test]
@@ -88,6 +135,6 @@ mod tests {
         let input = vec![
             "2-4,6-8", "2-3,4-5", "5-7,7-9", "2-8,3-7", "6-6,4-6", "2-6,4-8",
         ];
-        assert_eq!(part2(&input), 4);
+        assert_eq!(part2(&input).unwrap(), 4);
     }
 }
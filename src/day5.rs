@@ -1,171 +1,657 @@
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
 
-pub fn solve<S: AsRef<str>>(input: Vec<S>, crane: &Crane) -> String {
-    let mut input: VecDeque<S> = input.into();
-    let (initial, moves) = parse(&mut input);
-    let mut stacks = parse_initial(&initial);
+use anyhow::{anyhow, bail, Result};
 
-    for move_ in moves {
-        let move_ = parse_move(&move_);
-        crane(&mut stacks, move_);
+use crate::error::{ParseError, Scanner};
+use crate::solution::Solution;
+
+pub type Crate = String;
+
+/// How many crates to move, then the stacks to move them from and to, counting from 1.
+pub type Move = (u32, u32, u32);
+
+/// Rearranges the stacks, each listed from its top crate down.
+pub trait Crane {
+    fn name(&self) -> &str;
+    /// Fails, leaving the stacks as they were, when the move can't be made.
+    fn apply(&self, stacks: &mut [VecDeque<Crate>], move_: Move) -> Result<()>;
+}
+
+// the indices of the stacks to move between, once there are enough crates to move
+fn check_move(stacks: &[VecDeque<Crate>], (amount, from, to): Move) -> Result<(usize, usize)> {
+    let index = |stack: u32| match stack as usize {
+        0 => None,
+        stack => (stack <= stacks.len()).then_some(stack - 1),
+    };
+    let from_index = index(from).ok_or_else(|| anyhow!("day 5: there's no stack {}", from))?;
+    let to_index = index(to).ok_or_else(|| anyhow!("day 5: there's no stack {}", to))?;
+    let height = stacks[from_index].len();
+    if height < amount as usize {
+        bail!(
+            "day 5: can't move {} crates from stack {} of {}",
+            amount,
+            from,
+            height
+        );
+    }
+    Ok((from_index, to_index))
+}
+
+/// Picks up one crate at a time, so the ones it moves end up reversed.
+pub struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn name(&self) -> &str {
+        "CrateMover 9000"
     }
 
-    headers(stacks)
+    fn apply(&self, stacks: &mut [VecDeque<Crate>], move_: Move) -> Result<()> {
+        let (from, to) = check_move(stacks, move_)?;
+        let lifted: Vec<Crate> = stacks[from].drain(0..move_.0 as usize).collect();
+        for item in lifted {
+            stacks[to].push_front(item);
+        }
+        Ok(())
+    }
+}
+
+/// Moves all the crates at once, keeping their order.
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn name(&self) -> &str {
+        "CrateMover 9001"
+    }
+
+    fn apply(&self, stacks: &mut [VecDeque<Crate>], move_: Move) -> Result<()> {
+        let (from, to) = check_move(stacks, move_)?;
+        let slice: Vec<Crate> = stacks[from].drain(0..move_.0 as usize).rev().collect();
+        let target = &mut stacks[to];
+        slice.into_iter().for_each(|i| target.push_front(i));
+        Ok(())
+    }
+}
+
+/// The cranes `solve` can pick from by name.
+pub struct Cranes {
+    cranes: BTreeMap<String, Box<dyn Crane>>,
+}
+
+impl Cranes {
+    pub fn new() -> Self {
+        let mut cranes = Cranes {
+            cranes: BTreeMap::new(),
+        };
+        cranes.register(Box::new(CrateMover9000));
+        cranes.register(Box::new(CrateMover9001));
+        cranes
+    }
+
+    pub fn register(&mut self, crane: Box<dyn Crane>) {
+        let name = crane.name().to_owned();
+        let previous = self.cranes.insert(name.clone(), crane);
+        assert!(previous.is_none(), "crane {:?} is registered twice", name);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Crane> {
+        self.cranes.get(name).map(|crane| crane.as_ref())
+    }
+}
+
+impl Default for Cranes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn solve<S: AsRef<str>>(input: Vec<S>, cranes: &Cranes, name: &str) -> Result<String> {
+    let crane = cranes
+        .get(name)
+        .ok_or_else(|| anyhow!("day 5: unknown crane {:?}", name))?;
+    rearrange(&parse(&input)?, crane)
+}
+
+fn rearrange(plan: &Plan, crane: &dyn Crane) -> Result<String> {
+    // there's always the starting layout, and a bad move is the last state
+    let last = plan.states(crane).last().unwrap()?;
+    Ok(last.headers())
 }
 
-fn headers(stacks: Vec<VecDeque<char>>) -> String {
-    let mut result = String::new();
-    for mut stack in stacks {
-        let maybe_item = stack.pop_front();
-        if let Some(item) = maybe_item {
-            result.push(item);
+/// The starting layout, then the layout after each move in turn. A move that can't be made
+/// ends it with an error.
+pub fn states<S: AsRef<str>>(input: Vec<S>, crane: &dyn Crane) -> Result<States<'_>, ParseError> {
+    Ok(parse(&input)?.states(crane))
+}
+
+/// The starting stacks and the moves to make on them.
+#[derive(Debug, Clone)]
+pub struct Plan {
+    stacks: Vec<VecDeque<Crate>>,
+    steps: Vec<Step>,
+}
+
+impl Plan {
+    pub fn states<'a>(&self, crane: &'a dyn Crane) -> States<'a> {
+        States {
+            stacks: Stacks(self.stacks.clone()),
+            steps: self.steps.clone().into(),
+            crane,
+            started: false,
+            failed: false,
+        }
+    }
+}
+
+// a move, with the line index and column of its amount to report it can't be made
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Step {
+    line: usize,
+    column: usize,
+    move_: Move,
+}
+
+pub struct States<'a> {
+    stacks: Stacks,
+    steps: VecDeque<Step>,
+    crane: &'a dyn Crane,
+    started: bool,
+    failed: bool,
+}
+
+impl Iterator for States<'_> {
+    type Item = Result<Stacks>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
+            return Some(Ok(self.stacks.clone()));
+        }
+        if self.failed {
+            return None;
+        }
+        let step = self.steps.pop_front()?;
+        let applied = check_height(&step, &self.stacks.0)
+            .map_err(anyhow::Error::from)
+            .and_then(|move_| self.crane.apply(&mut self.stacks.0, move_));
+        match applied {
+            Ok(()) => Some(Ok(self.stacks.clone())),
+            Err(error) => {
+                self.failed = true;
+                Some(Err(error))
+            }
         }
     }
-    result
 }
 
-fn parse<S>(input: &mut VecDeque<S>) -> (Vec<String>, &VecDeque<S>)
-where
-    S: AsRef<str>,
-{
-    let mut initial = Vec::new();
+/// Each stack from its top crate down; displayed the way the puzzle input draws them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stacks(pub Vec<VecDeque<Crate>>);
 
-    while let Some(item) = input.pop_front() {
-        if !item.as_ref().is_empty() {
-            initial.push(item.as_ref().to_owned());
-        } else {
-            break;
+impl Stacks {
+    /// The crate on top of each stack, skipping empty ones.
+    pub fn headers(&self) -> String {
+        let tops = self.0.iter().filter_map(|stack| stack.front());
+        tops.map(|name| name.as_str()).collect()
+    }
+}
+
+// every column is as wide as the widest crate or label, so the labels still sit under them
+impl fmt::Display for Stacks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let height = self.0.iter().map(|stack| stack.len()).max().unwrap_or(0);
+        let names = self.0.iter().flatten().map(|name| name.chars().count() + 2);
+        let width = names
+            .chain([3, self.0.len().to_string().len()])
+            .max()
+            .unwrap();
+        for row in 0..height {
+            let cells: Vec<String> = self
+                .0
+                .iter()
+                .map(|stack| match (row + stack.len()).checked_sub(height) {
+                    Some(i) => format!("{:<width$}", format!("[{}]", stack[i])),
+                    None => " ".repeat(width),
+                })
+                .collect();
+            writeln!(f, "{}", cells.join(" ").trim_end())?;
         }
+        let labels: Vec<String> = (1..=self.0.len())
+            .map(|i| format!("{:^width$}", i))
+            .collect();
+        write!(f, "{}", labels.join(" "))
     }
+}
 
-    (initial, input)
+fn parse<S: AsRef<str>>(input: &[S]) -> Result<Plan, ParseError> {
+    let is_blank = |line: &S| line.as_ref().trim().is_empty();
+    let first = input.iter().position(|line| !is_blank(line)).unwrap_or(0);
+    let labels = input.iter().skip(first).position(|line| {
+        let line = line.as_ref().trim_start();
+        line.starts_with(|c: char| c.is_ascii_digit())
+    });
+    let Some(labels) = labels.map(|i| first + i) else {
+        let expected = "a line of stack labels";
+        return Err(ParseError::new(
+            Day5::DAY,
+            input.len() + 1,
+            1,
+            expected,
+            "end of input",
+        ));
+    };
+    let stacks = parse_initial(first, &input[first..=labels])?;
+    let mut steps = Vec::new();
+    for (i, line) in input.iter().enumerate().skip(labels + 1) {
+        if !is_blank(line) {
+            steps.push(parse_move(i, line.as_ref(), stacks.len())?);
+        }
+    }
+    Ok(Plan { stacks, steps })
 }
 
-fn parse_initial<S: AsRef<str>>(initial: &Vec<S>) -> Vec<VecDeque<char>> {
-    let initial_len = initial.len();
-    let max_count = count(&initial[initial_len - 1]);
-    let mut result = vec![VecDeque::new(); max_count];
-    for line in initial[0..initial.len() - 1].iter() {
-        let line = line.as_ref();
-        let chars: Vec<char> = line.chars().collect();
-        let count = count(line);
-        for (i, item) in result.iter_mut().enumerate().take(count) {
-            let pos = 4 * i + 1;
-            if chars[pos] != ' ' {
-                item.push_back(chars[pos]);
+// the drawing starting at line index `first`, labels last
+fn parse_initial<S: AsRef<str>>(
+    first: usize,
+    initial: &[S],
+) -> Result<Vec<VecDeque<Crate>>, ParseError> {
+    let (labels, rows) = initial
+        .split_last()
+        .ok_or_else(|| ParseError::new(Day5::DAY, first + 1, 1, "a stack drawing", "empty line"))?;
+    let labels = parse_labels(first + rows.len(), labels.as_ref())?;
+    let mut result = vec![VecDeque::new(); labels.len()];
+    for (row, line) in rows.iter().enumerate() {
+        let mut scanner = Scanner::new(Day5::DAY, first + row, line.as_ref());
+        let mut filled = vec![false; labels.len()];
+        loop {
+            scanner.whitespace();
+            if scanner.is_empty() {
+                break;
+            }
+            let at = scanner.clone();
+            scanner.tag("[")?;
+            let at_name = scanner.clone();
+            let name = scanner.until(']');
+            if name.is_empty() {
+                return Err(at_name.error("a crate name"));
             }
+            scanner.tag("]")?;
+            // a crate belongs to the one label it sits above
+            let (start, end) = (at.column(), scanner.column());
+            let mut below = (0..labels.len()).filter(|i| {
+                let (from, to) = labels[*i];
+                from < end && start < to
+            });
+            match (below.next(), below.next()) {
+                (Some(stack), None) if !filled[stack] => {
+                    filled[stack] = true;
+                    result[stack].push_back(name.to_owned());
+                }
+                _ => return Err(at.error("a crate above one free stack label")),
+            }
+        }
+    }
+    Ok(result)
+}
+
+// the columns each label covers, as [first, one past the last)
+fn parse_labels(line: usize, text: &str) -> Result<Vec<(usize, usize)>, ParseError> {
+    let mut scanner = Scanner::new(Day5::DAY, line, text);
+    let mut result = Vec::new();
+    loop {
+        scanner.whitespace();
+        if scanner.is_empty() {
+            return Ok(result);
+        }
+        let at = scanner.clone();
+        let expected = format!("stack {}", result.len() + 1);
+        let label: usize = scanner.number().map_err(|_| at.error(&expected))?;
+        if label != result.len() + 1 || !(scanner.is_empty() || scanner.whitespace()) {
+            return Err(at.error(&expected));
         }
+        result.push((at.column(), at.column() + label.to_string().len()));
     }
+}
+
+fn parse_move(i: usize, move_: &str, stacks: usize) -> Result<Step, ParseError> {
+    let mut scanner = Scanner::new(Day5::DAY, i, move_);
+    scanner.whitespace();
+    scanner.tag("move")?;
+    gap(&mut scanner)?;
+    let column = scanner.column();
+    let amount: u32 = scanner.number()?;
+    gap(&mut scanner)?;
+    scanner.tag("from")?;
+    gap(&mut scanner)?;
+    let from = stack_number(&mut scanner, stacks)?;
+    gap(&mut scanner)?;
+    scanner.tag("to")?;
+    gap(&mut scanner)?;
+    let to = stack_number(&mut scanner, stacks)?;
+    scanner.whitespace();
+    scanner.end()?;
 
-    result
+    Ok(Step {
+        line: i,
+        column,
+        move_: (amount, from, to),
+    })
 }
 
-fn count<S: AsRef<str>>(line: S) -> usize {
-    (line.as_ref().len() + 1) / 4
+fn check_height(step: &Step, stacks: &[VecDeque<Crate>]) -> Result<Move, ParseError> {
+    let (amount, from, _) = step.move_;
+    let height = stacks[(from - 1) as usize].len();
+    if amount as usize > height {
+        let expected = format!("at most {} crates", height);
+        let found = amount.to_string();
+        return Err(ParseError::new(
+            Day5::DAY,
+            step.line + 1,
+            step.column,
+            &expected,
+            &found,
+        ));
+    }
+    Ok(step.move_)
 }
 
-fn parse_move(move_: &dyn AsRef<str>) -> (u32, u32, u32) {
-    let mut tokens = move_.as_ref().split(' ');
-    let amount = tokens.nth(1).unwrap();
-    let from = tokens.nth(1).unwrap();
-    let to = tokens.nth(1).unwrap();
-    (
-        amount.parse().unwrap(),
-        from.parse().unwrap(),
-        to.parse().unwrap(),
-    )
+fn gap(scanner: &mut Scanner) -> Result<(), ParseError> {
+    if scanner.whitespace() {
+        Ok(())
+    } else {
+        Err(scanner.error("a space"))
+    }
+}
+
+fn stack_number(scanner: &mut Scanner, stacks: usize) -> Result<u32, ParseError> {
+    let at_stack = scanner.clone();
+    let stack: u32 = scanner.number()?;
+    if stack == 0 || stack as usize > stacks {
+        return Err(at_stack.error(&format!("a stack from 1 to {}", stacks)));
+    }
+    Ok(stack)
 }
 
-type Crane = dyn Fn(&mut [VecDeque<char>], (u32, u32, u32));
+pub struct Day5;
+
+impl Solution for Day5 {
+    const YEAR: u32 = 2022;
+    const DAY: u32 = 5;
+
+    type Input = Plan;
+    type Part1 = String;
+    type Part2 = String;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let input: Vec<&str> = input.lines().collect();
+        Ok(parse(&input)?)
+    }
+
+    fn part1(plan: &Self::Input) -> Result<String> {
+        rearrange(plan, &CrateMover9000)
+    }
+
+    fn part2(plan: &Self::Input) -> Result<String> {
+        rearrange(plan, &CrateMover9001)
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::util;
 
-    fn apply_move(stacks: &mut [VecDeque<char>], (amount, from, to): (u32, u32, u32)) {
-        for _ in 0..amount {
-            pop_once(stacks, (from, to));
-        }
+    const EXAMPLE: [&str; 9] = [
+        "    [D]",
+        "[N] [C]",
+        "[Z] [M] [P]",
+        " 1   2   3 ",
+        "",
+        "move 1 from 2 to 1",
+        "move 3 from 1 to 3",
+        "move 2 from 2 to 1",
+        "move 1 from 1 to 2",
+    ];
+
+    // lifts up to `batch` crates at a time, so the batches land in reverse order
+    struct Batched {
+        batch: u32,
     }
 
-    fn pop_once(stacks: &mut [VecDeque<char>], (from, to): (u32, u32)) {
-        let item = stacks[(from - 1) as usize].pop_front().unwrap();
-        stacks[(to - 1) as usize].push_front(item);
+    impl Crane for Batched {
+        fn name(&self) -> &str {
+            "batched"
+        }
+
+        fn apply(&self, stacks: &mut [VecDeque<Crate>], (amount, from, to): Move) -> Result<()> {
+            check_move(stacks, (amount, from, to))?;
+            let mut left = amount;
+            while left > 0 {
+                let batch = left.min(self.batch);
+                CrateMover9001.apply(stacks, (batch, from, to))?;
+                left -= batch;
+            }
+            Ok(())
+        }
     }
 
-    fn new_crane(stacks: &mut [VecDeque<char>], (amount, from, to): (u32, u32, u32)) {
-        let source = stacks.get_mut((from - 1) as usize).unwrap();
-        let slice: Vec<char> = source
-            .drain(0..(amount) as usize)
-            .rev()
-            .collect();
-        let target = stacks.get_mut((to - 1) as usize).unwrap();
-        slice.iter().for_each(|i| target.push_front(*i));
+    // one letter crates, each stack from the top
+    fn stacks(stacks: &[&str]) -> Vec<VecDeque<Crate>> {
+        let stack = |crates: &&str| crates.chars().map(String::from).collect();
+        stacks.iter().map(stack).collect()
     }
 
     #[test]
     fn test_parse_initial() {
         let initial = vec!["    [D]", "[N] [C]", "[Z] [M] [P]", " 1   2   3 "];
-        let actual = parse_initial(&initial);
-        assert_eq!(actual.len(), 3);
+        let actual = parse_initial(0, &initial).unwrap();
+        assert_eq!(actual, stacks(&["NZ", "DCM", "P"]));
+    }
 
-        let second: &VecDeque<char> = &actual[1];
-        let expected: VecDeque<char> = vec!['D', 'C', 'M'].into();
-        assert_eq!(second, &expected);
+    #[test]
+    fn test_parse_irregular() {
+        // trimmed, double digit labels, long names and extra blank lines
+        let row = format!("[B]{}[Ten]", " ".repeat(33));
+        let mut input = vec!["", "[A]", &row];
+        let labels = " 1   2   3   4   5   6   7   8   9  10";
+        input.extend([
+            labels,
+            "",
+            "",
+            "move 1 from 10 to 1",
+            "",
+            "  move  1 from 1\tto 2  ",
+        ]);
+        let mut states = states(input, &CrateMover9000).unwrap();
+        let start = states.next().unwrap().unwrap();
+        let mut expected = stacks(&["AB", "", "", "", "", "", "", "", "", ""]);
+        expected[9].push_back("Ten".to_owned());
+        assert_eq!(start.0, expected);
+        let last = states.last().unwrap().unwrap();
+        assert_eq!(last.headers(), "ATen");
+        let expected = [
+            "[A]",
+            "[B]   [Ten]",
+            "  1     2     3     4     5     6     7     8     9    10  ",
+        ];
+        assert_eq!(last.to_string(), expected.join("\n"));
+        let reparsed: Vec<String> = last.to_string().lines().map(String::from).collect();
+        assert_eq!(parse_initial(0, &reparsed).unwrap(), last.0);
+    }
+
+    #[test]
+    fn test_parse_drawing_error() {
+        let error = parse_initial(2, &["[A]   [B]", " 1   2"]).unwrap_err();
+        let expected = "a crate above one free stack label";
+        assert_eq!(error, ParseError::new(5, 3, 7, expected, "[B]"));
+
+        let error = parse_initial(0, &["[A] [] ", " 1   2"]).unwrap_err();
+        assert_eq!(error, ParseError::new(5, 1, 6, "a crate name", "]"));
+
+        let error = parse_initial(0, &["[A]  B", " 1   2"]).unwrap_err();
+        assert_eq!(error, ParseError::new(5, 1, 6, "\"[\"", "B"));
+
+        let error = parse_initial(0, &["[A]", " 1   3"]).unwrap_err();
+        assert_eq!(error, ParseError::new(5, 2, 6, "stack 2", "3"));
+
+        let error = parse_initial(0, &["[A]", " 1x"]).unwrap_err();
+        assert_eq!(error, ParseError::new(5, 2, 2, "stack 1", "1x"));
+
+        let error = states(vec!["[A]", "", "move 1 from 1 to 1"], &CrateMover9000).err();
+        let expected = "a line of stack labels";
+        assert_eq!(
+            error,
+            Some(ParseError::new(5, 4, 1, expected, "end of input"))
+        );
     }
 
     #[test]
     fn test_solve() {
-        let input = vec![
-            "    [D]",
-            "[N] [C]",
-            "[Z] [M] [P]",
+        let cranes = Cranes::new();
+        assert_eq!(
+            solve(EXAMPLE.into(), &cranes, "CrateMover 9000").unwrap(),
+            "CMZ"
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let mut states = states(EXAMPLE.into(), &CrateMover9000).unwrap();
+        let start = states.next().unwrap().unwrap();
+        assert_eq!(start.to_string(), EXAMPLE[..4].join("\n"));
+
+        let last = states.last().unwrap().unwrap();
+        let expected = [
+            "        [Z]",
+            "        [N]",
+            "        [D]",
+            "[C] [M] [P]",
+            " 1   2   3 ",
+        ];
+        assert_eq!(last.to_string(), expected.join("\n"));
+        assert_eq!(last.headers(), "CMZ");
+    }
+
+    #[test]
+    fn test_states() {
+        let mut input = EXAMPLE.to_vec();
+        input[7] = "move 3 from 2 to 1";
+        let states: Vec<_> = states(input, &CrateMover9001).unwrap().collect();
+        assert_eq!(states.len(), 4);
+        let expected = [
+            "        [D]",
+            "        [N]",
+            "    [C] [Z]",
+            "    [M] [P]",
             " 1   2   3 ",
-            "",
-            "move 1 from 2 to 1",
-            "move 3 from 1 to 3",
-            "move 2 from 2 to 1",
-            "move 1 from 1 to 2",
         ];
-        assert_eq!(solve(input, &apply_move), "CMZ");
+        assert_eq!(states[2].as_ref().unwrap().to_string(), expected.join("\n"));
+        let error = ParseError::new(5, 8, 6, "at most 2 crates", "3");
+        let actual = states[3].as_ref().unwrap_err().downcast_ref::<ParseError>();
+        assert_eq!(actual, Some(&error));
+    }
+
+    #[test]
+    fn test_parse_move_error() {
+        let stacks = stacks(&["DN", "C"]);
+        let step = parse_move(6, "move 2 from 1 to 2", 2).unwrap();
+        assert_eq!(check_height(&step, &stacks), Ok((2, 1, 2)));
+
+        let step = parse_move(6, "move 2 from 2 to 1", 2).unwrap();
+        let error = check_height(&step, &stacks).unwrap_err();
+        assert_eq!(error, ParseError::new(5, 7, 6, "at most 1 crates", "2"));
+
+        let error = parse_move(6, "move 1 from 1 to 3", 2).unwrap_err();
+        assert_eq!(error, ParseError::new(5, 7, 18, "a stack from 1 to 2", "3"));
+
+        let error = parse_move(6, "move 1 fr 1 to 2", 2).unwrap_err();
+        assert_eq!(error, ParseError::new(5, 7, 8, "\"from\"", "fr"));
+
+        let error = parse_move(6, "move 1from 1 to 2", 2).unwrap_err();
+        assert_eq!(error, ParseError::new(5, 7, 7, "a space", "from"));
+
+        let error = parse_move(6, " move 1 from 1 to 2 x", 2).unwrap_err();
+        assert_eq!(error, ParseError::new(5, 7, 21, "end of line", "x"));
     }
 
     #[test]
     fn test_new_crane() {
-        let mut stacks: Vec<VecDeque<char>> =
-            vec![['D', 'N', 'Z'].into(), ['C', 'M'].into(), ['P'].into()];
-        new_crane(&mut stacks, (3, 1, 3));
-        let expected: Vec<VecDeque<char>> =
-            vec![[].into(), ['C', 'M'].into(), ['D', 'N', 'Z', 'P'].into()];
-        assert_eq!(stacks, expected);
+        let mut stacks = stacks(&["DNZ", "CM", "P"]);
+        CrateMover9001.apply(&mut stacks, (3, 1, 3)).unwrap();
+        assert_eq!(stacks, self::stacks(&["", "CM", "DNZP"]));
+    }
+
+    #[test]
+    fn test_apply_error() {
+        let mut stacks = stacks(&["DN", "C"]);
+        let error = CrateMover9000.apply(&mut stacks, (3, 1, 2)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 5: can't move 3 crates from stack 1 of 2"
+        );
+        let error = CrateMover9001.apply(&mut stacks, (1, 1, 3)).unwrap_err();
+        assert_eq!(error.to_string(), "day 5: there's no stack 3");
+        let error = Batched { batch: 1 }
+            .apply(&mut stacks, (2, 2, 1))
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 5: can't move 2 crates from stack 2 of 1"
+        );
+        assert_eq!(stacks, self::stacks(&["DN", "C"]));
     }
 
     #[test]
     fn test_solve_with_new_crane() {
-        let input = vec![
-            "    [D]",
-            "[N] [C]",
-            "[Z] [M] [P]",
-            " 1   2   3 ",
-            "",
-            "move 1 from 2 to 1",
-            "move 3 from 1 to 3",
-            "move 2 from 2 to 1",
-            "move 1 from 1 to 2",
-        ];
-        assert_eq!(solve(input, &new_crane), "MCD");
+        let cranes = Cranes::new();
+        assert_eq!(
+            solve(EXAMPLE.into(), &cranes, "CrateMover 9001").unwrap(),
+            "MCD"
+        );
+    }
+
+    #[test]
+    fn test_custom_crane() {
+        let mut cranes = Cranes::new();
+        cranes.register(Box::new(Batched { batch: 2 }));
+        let mut stacks = stacks(&["ABCDE", ""]);
+        cranes
+            .get("batched")
+            .unwrap()
+            .apply(&mut stacks, (5, 1, 2))
+            .unwrap();
+        assert_eq!(stacks, self::stacks(&["", "ECDAB"]));
+
+        // one crate at a time is the old crane
+        let mut cranes = Cranes::new();
+        cranes.register(Box::new(Batched { batch: 1 }));
+        let input = util::read_real_data("day5");
+        assert_eq!(solve(input, &cranes, "batched").unwrap(), "DHBJQJCCW");
+
+        let error = solve(EXAMPLE.into(), &cranes, "CrateMover 9002").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 5: unknown crane \"CrateMover 9002\""
+        );
     }
 
     #[test]
     fn test_new_crane_with_real_data() {
         let input = util::read_real_data("day5");
-        assert_eq!(solve(input, &new_crane), "WJVRLSJJT");
+        let cranes = Cranes::new();
+        assert_eq!(
+            solve(input, &cranes, "CrateMover 9001").unwrap(),
+            "WJVRLSJJT"
+        );
     }
 
     #[test]
     fn test_real_data() {
         let input = util::read_real_data("day5");
-        assert_eq!(solve(input, &apply_move), "DHBJQJCCW");
+        let cranes = Cranes::new();
+        assert_eq!(
+            solve(input, &cranes, "CrateMover 9000").unwrap(),
+            "DHBJQJCCW"
+        );
     }
 }
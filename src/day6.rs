@@ -1,20 +1,107 @@
-use std::collections::HashSet;
 use std::collections::VecDeque;
+use std::io::{self, BufReader, Read};
 
-pub fn solve(input: &str, window_size: usize) -> usize {
-    let mut result: usize = window_size;
-    let mut window: VecDeque<char> = input.chars().take(window_size).collect();
-    while !is_marker(&window, window_size) {
-        window.pop_front();
-        window.push_back(input.chars().nth(result).unwrap());
-        result += 1;
+use anyhow::Result;
+
+use crate::error::ParseError;
+use crate::solution::Solution;
+
+pub fn solve(input: &str, window_size: usize) -> Result<usize> {
+    let marker = first_marker(input.bytes(), window_size).ok_or_else(|| {
+        let expected = format!("a marker of {} distinct characters", window_size);
+        let column = input.chars().count() + 1;
+        ParseError::new(Day6::DAY, 1, column, &expected, "end of line")
+    })?;
+    Ok(marker)
+}
+
+/// How many bytes have been read at the end of the first marker, if there is one.
+pub fn first_marker<I: IntoIterator<Item = u8>>(bytes: I, window_size: usize) -> Option<usize> {
+    markers(bytes, window_size).next()
+}
+
+/// Every marker in a stream, failing if reading it does.
+pub fn read_markers<R: Read>(reader: R, window_size: usize) -> io::Result<Vec<usize>> {
+    let mut error = None;
+    let bytes = BufReader::new(reader)
+        .bytes()
+        .map_while(|byte| byte.map_err(|e| error = Some(e)).ok());
+    let result = markers(bytes, window_size).collect();
+    match error {
+        Some(error) => Err(error),
+        None => Ok(result),
+    }
+}
+
+/// How many bytes have been read at the end of each window of `window_size` distinct ones.
+pub fn markers<I: IntoIterator<Item = u8>>(bytes: I, window_size: usize) -> Markers<I::IntoIter> {
+    Markers {
+        bytes: bytes.into_iter(),
+        window_size,
+        window: VecDeque::with_capacity(window_size + 1),
+        counts: [0; 256],
+        distinct: 0,
+        position: 0,
+    }
+}
+
+pub struct Markers<I> {
+    bytes: I,
+    window_size: usize,
+    window: VecDeque<u8>,
+    // how often each byte is in the window
+    counts: [usize; 256],
+    distinct: usize,
+    position: usize,
+}
+
+impl<I: Iterator<Item = u8>> Iterator for Markers<I> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        loop {
+            let byte = self.bytes.next()?;
+            self.position += 1;
+            self.window.push_back(byte);
+            self.counts[byte as usize] += 1;
+            if self.counts[byte as usize] == 1 {
+                self.distinct += 1;
+            }
+            if self.window.len() > self.window_size {
+                let old = self.window.pop_front().unwrap();
+                self.counts[old as usize] -= 1;
+                if self.counts[old as usize] == 0 {
+                    self.distinct -= 1;
+                }
+            }
+            if self.window.len() == self.window_size && self.distinct == self.window_size {
+                return Some(self.position);
+            }
+        }
     }
-    result
 }
 
-fn is_marker(window: &VecDeque<char>, window_size: usize) -> bool {
-    let distinct: HashSet<&char> = window.iter().collect();
-    distinct.len() == window_size
+pub struct Day6;
+
+impl Solution for Day6 {
+    const YEAR: u32 = 2022;
+    const DAY: u32 = 6;
+
+    type Input = String;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(input.lines().next().unwrap_or_default().to_owned())
+    }
+
+    fn part1(input: &Self::Input) -> Result<usize> {
+        solve(input, 4)
+    }
+
+    fn part2(input: &Self::Input) -> Result<usize> {
+        solve(input, 14)
+    }
 }
 
 #[cfg(test)]
@@ -27,11 +114,46 @@ mod tests {
 
     #[test]
     fn test_solve() {
-        assert_eq!(solve("mjqjpqmgbljsphdztnvjfqwrcgsmlb", MARKER_SIZE), 7);
-        assert_eq!(solve("bvwbjplbgvbhsrlpgdmjqwftvncz", MARKER_SIZE), 5);
-        assert_eq!(solve("nppdvjthqldpwncqszvftbrmjlhg", MARKER_SIZE), 6);
-        assert_eq!(solve("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", MARKER_SIZE), 10);
-        assert_eq!(solve("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", MARKER_SIZE), 11);
+        assert_eq!(
+            solve("mjqjpqmgbljsphdztnvjfqwrcgsmlb", MARKER_SIZE).unwrap(),
+            7
+        );
+        assert_eq!(
+            solve("bvwbjplbgvbhsrlpgdmjqwftvncz", MARKER_SIZE).unwrap(),
+            5
+        );
+        assert_eq!(
+            solve("nppdvjthqldpwncqszvftbrmjlhg", MARKER_SIZE).unwrap(),
+            6
+        );
+        assert_eq!(
+            solve("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", MARKER_SIZE).unwrap(),
+            10
+        );
+        assert_eq!(
+            solve("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", MARKER_SIZE).unwrap(),
+            11
+        );
+    }
+
+    #[test]
+    fn test_no_marker() {
+        let error = solve("abcabc", MARKER_SIZE).unwrap_err();
+        let error = error.downcast::<ParseError>().unwrap();
+        assert_eq!((error.line, error.column), (1, 7));
+    }
+
+    #[test]
+    fn test_markers() {
+        let all: Vec<usize> = markers("abcabdd".bytes(), 3).collect();
+        assert_eq!(all, vec![3, 4, 5, 6]);
+        assert_eq!(first_marker("aab".bytes(), 2), Some(3));
+        assert_eq!(first_marker("abcabc".bytes(), MARKER_SIZE), None);
+        assert_eq!(first_marker([], MARKER_SIZE), None);
+
+        let stream = "mjqjpqmgbljsph".as_bytes();
+        let all = read_markers(stream, MARKER_SIZE).unwrap();
+        assert_eq!(all, vec![7, 8, 9, 10, 11, 12, 13, 14]);
     }
 
     #[test]
@@ -39,16 +161,31 @@ mod tests {
         let input = util::read_real_data("day6");
         let input = &input[0];
 
-        assert_eq!(solve(input, MARKER_SIZE), 1598);
+        assert_eq!(solve(input, MARKER_SIZE).unwrap(), 1598);
     }
 
     #[test]
     fn test_solve_part2() {
-        assert_eq!(solve("mjqjpqmgbljsphdztnvjfqwrcgsmlb", MESSAGE_SIZE), 19);
-        assert_eq!(solve("bvwbjplbgvbhsrlpgdmjqwftvncz", MESSAGE_SIZE), 23);
-        assert_eq!(solve("nppdvjthqldpwncqszvftbrmjlhg", MESSAGE_SIZE), 23);
-        assert_eq!(solve("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", MESSAGE_SIZE), 29);
-        assert_eq!(solve("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", MESSAGE_SIZE), 26);
+        assert_eq!(
+            solve("mjqjpqmgbljsphdztnvjfqwrcgsmlb", MESSAGE_SIZE).unwrap(),
+            19
+        );
+        assert_eq!(
+            solve("bvwbjplbgvbhsrlpgdmjqwftvncz", MESSAGE_SIZE).unwrap(),
+            23
+        );
+        assert_eq!(
+            solve("nppdvjthqldpwncqszvftbrmjlhg", MESSAGE_SIZE).unwrap(),
+            23
+        );
+        assert_eq!(
+            solve("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", MESSAGE_SIZE).unwrap(),
+            29
+        );
+        assert_eq!(
+            solve("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", MESSAGE_SIZE).unwrap(),
+            26
+        );
     }
 
     #[test]
@@ -56,6 +193,6 @@ mod tests {
         let input = util::read_real_data("day6");
         let input = &input[0];
 
-        assert_eq!(solve(input, MESSAGE_SIZE), 2414);
+        assert_eq!(solve(input, MESSAGE_SIZE).unwrap(), 2414);
     }
 }
@@ -1,21 +1,44 @@
+use std::cell::OnceCell;
+use std::cmp::Reverse;
 use std::collections::HashMap;
 use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{env, process};
+
+use anyhow::{bail, Result};
+
+use crate::error::{ParseError, Scanner};
+use crate::solution::Solution;
 
 type Arena = Vec<Entry>;
 type Inode = usize;
 
-pub fn solve(mut input: VecDeque<&str>) -> usize {
-    let fs = FileSystem::new();
-    let mut interpreter = Interpreter { fs };
-    interpreter.traverse(&mut input);
-    interpreter.estimate_cleanup_space()
+pub const DISK: usize = 70000000;
+pub const REQUIRED: usize = 30000000;
+
+pub fn solve(input: VecDeque<&str>) -> Result<usize> {
+    Day7::part1(&replay(&Vec::from(input))?)
+}
+
+pub fn part2(input: VecDeque<&str>) -> Result<usize> {
+    Day7::part2(&replay(&Vec::from(input))?)
 }
 
-pub fn part2(mut input: VecDeque<&str>) -> usize {
+/// Replays a terminal transcript and returns the filesystem it leaves behind.
+pub fn replay(input: &[&str]) -> Result<FileSystem, ParseError> {
     let fs = FileSystem::new();
-    let mut interpreter = Interpreter { fs };
-    interpreter.traverse(&mut input);
-    interpreter.part2()
+    let mut interpreter = Interpreter { fs, line: 0 };
+    interpreter.traverse(&mut input.iter().copied().collect())?;
+    Ok(interpreter.fs)
+}
+
+/// A directory that could be deleted and how much deleting it frees.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub path: String,
+    pub size: usize,
 }
 
 #[derive(PartialEq, Debug)]
@@ -24,6 +47,15 @@ enum Type {
     Dir,
 }
 
+impl Type {
+    fn name(&self) -> &'static str {
+        match self {
+            Type::File => "file",
+            Type::Dir => "dir",
+        }
+    }
+}
+
 #[derive(Debug)]
 struct Entry {
     name: String,
@@ -52,10 +84,14 @@ impl Entry {
     }
 }
 
-struct FileSystem {
+// removed entries stay in the arena, out of reach from the root
+pub struct FileSystem {
     arena: Arena,
     cwd: Inode,
     child_to_parent: HashMap<Inode, Inode>,
+    children: Vec<HashMap<String, Inode>>,
+    // total size per entry, None once removed; dropped on every change
+    sizes: OnceCell<Vec<Option<usize>>>,
 }
 
 impl FileSystem {
@@ -68,161 +104,569 @@ impl FileSystem {
             cwd: 0,
             arena,
             child_to_parent: HashMap::new(),
+            children: vec![HashMap::new()],
+            sizes: OnceCell::new(),
         }
     }
 
-    fn cd(&mut self, dir: &str) {
-        if dir == ".." {
-            self.cwd = *self.child_to_parent.get(&self.cwd).unwrap();
-            return;
+    /// Looks up an absolute path or one relative to the working directory.
+    fn resolve(&self, path: &str) -> Option<Inode> {
+        let mut current = if path.starts_with('/') { 0 } else { self.cwd };
+        for part in path
+            .split('/')
+            .filter(|part| !part.is_empty() && *part != ".")
+        {
+            current = if part == ".." {
+                *self.child_to_parent.get(&current)?
+            } else {
+                *self.ls(current).get(part)?
+            };
         }
+        Some(current)
+    }
 
-        let children = self.ls_cwd();
-        self.cwd = if let Some(entry) = children.get(dir) {
-            entry.address
-        } else {
-            self.mkdir(dir)
+    // the directory a new entry at `path` goes into, and its name
+    fn split_path<'p>(&self, path: &'p str) -> Option<(Inode, &'p str)> {
+        let path = path.trim_end_matches('/');
+        let (dir, name) = match path.rfind('/') {
+            Some(i) => (self.resolve(&path[..=i])?, &path[i + 1..]),
+            None => (self.cwd, path),
+        };
+        if name.is_empty() || name == "." || name == ".." || !self.is_dir(dir) {
+            return None;
         }
+        Some((dir, name))
     }
 
-    fn ls(&self, dir: Inode) -> HashMap<String, &Entry> {
-        self.arena
-            .iter()
-            .filter(|entry| self.child_to_parent.get(&entry.address) == Some(&dir))
-            .map(|entry| (entry.name.to_owned(), entry))
-            .collect()
+    fn is_dir(&self, inode: Inode) -> bool {
+        self.arena[inode].entry_type == Type::Dir
     }
 
-    fn ls_cwd(&self) -> HashMap<String, &Entry> {
-        self.ls(self.cwd)
+    fn is_ancestor(&self, ancestor: Inode, mut inode: Inode) -> bool {
+        loop {
+            if inode == ancestor {
+                return true;
+            }
+            match self.child_to_parent.get(&inode) {
+                Some(parent) => inode = *parent,
+                None => return false,
+            }
+        }
     }
 
-    fn touch(&mut self, name: &str, size: usize) -> Inode {
-        let result = self.arena.len();
-        self.arena.push(Entry::new_file(name, result, size));
-        self.child_to_parent.insert(result, self.cwd);
-        result
+    pub fn cd(&mut self, path: &str) -> bool {
+        match self.resolve(path) {
+            Some(inode) if self.is_dir(inode) => {
+                self.cwd = inode;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn pwd(&self) -> String {
+        self.path(self.cwd)
+    }
+
+    fn path(&self, mut inode: Inode) -> String {
+        let mut names = Vec::new();
+        while let Some(parent) = self.child_to_parent.get(&inode) {
+            names.push(self.arena[inode].name.as_str());
+            inode = *parent;
+        }
+        names.reverse();
+        format!("/{}", names.join("/"))
+    }
+
+    fn ls(&self, dir: Inode) -> &HashMap<String, Inode> {
+        &self.children[dir]
     }
 
-    fn mkdir(&mut self, name: &str) -> Inode {
+    fn create(&mut self, dir: Inode, name: &str, size: Option<usize>) -> Inode {
+        if let Some(child) = self.ls(dir).get(name) {
+            return *child;
+        }
         let result = self.arena.len();
-        self.arena.push(Entry::new_dir(name, result));
-        self.child_to_parent.insert(result, self.cwd);
+        let entry = match size {
+            Some(size) => Entry::new_file(name, result, size),
+            None => Entry::new_dir(name, result),
+        };
+        self.arena.push(entry);
+        self.children.push(HashMap::new());
+        self.attach(result, dir);
         result
     }
 
-    fn du(&self, inode: Inode) -> usize {
-        let entry = &self.arena[inode];
-        match entry.entry_type {
-            Type::File => entry.size,
-            Type::Dir => {
-                let children = self.ls(inode);
-                let mut result = 0;
-                for child in children.values() {
-                    let size = self.du(child.address);
-                    result += size;
-                }
-                result
+    fn attach(&mut self, inode: Inode, dir: Inode) {
+        let name = self.arena[inode].name.clone();
+        self.children[dir].insert(name, inode);
+        self.child_to_parent.insert(inode, dir);
+        self.sizes.take();
+    }
+
+    fn detach(&mut self, inode: Inode) {
+        if let Some(parent) = self.child_to_parent.remove(&inode) {
+            self.children[parent].remove(&self.arena[inode].name);
+        }
+        self.sizes.take();
+    }
+
+    /// Creates an empty file unless something is already there.
+    pub fn touch(&mut self, path: &str) -> Option<Inode> {
+        let (dir, name) = self.split_path(path)?;
+        Some(self.create(dir, name, Some(0)))
+    }
+
+    pub fn mkdir(&mut self, path: &str) -> Option<Inode> {
+        let (dir, name) = self.split_path(path)?;
+        let inode = self.create(dir, name, None);
+        self.is_dir(inode).then_some(inode)
+    }
+
+    /// Removes a file, or with `recursive` a directory and everything in it. The root and
+    /// the directories leading to the working directory can't be removed.
+    pub fn rm(&mut self, path: &str, recursive: bool) -> bool {
+        let Some(inode) = self.resolve(path) else {
+            return false;
+        };
+        if self.is_ancestor(inode, self.cwd) || (self.is_dir(inode) && !recursive) {
+            return false;
+        }
+        self.detach(inode);
+        true
+    }
+
+    /// Moves `from` into the directory `to`, or renames it to `to`. A file there is only
+    /// replaced by another file.
+    pub fn mv(&mut self, from: &str, to: &str) -> bool {
+        let Some(source) = self.resolve(from) else {
+            return false;
+        };
+        let target = match self.resolve(to) {
+            Some(target) if self.is_dir(target) => Some((target, self.arena[source].name.clone())),
+            Some(target) => self
+                .child_to_parent
+                .get(&target)
+                .map(|parent| (*parent, self.arena[target].name.clone())),
+            None => self
+                .split_path(to)
+                .map(|(dir, name)| (dir, name.to_owned())),
+        };
+        let Some((dir, name)) = target else {
+            return false;
+        };
+        if source == 0 || self.is_ancestor(source, dir) {
+            return false;
+        }
+        if let Some(existing) = self.ls(dir).get(&name).copied() {
+            if existing == source {
+                return true;
+            }
+            if self.is_dir(existing) || self.is_dir(source) {
+                return false;
             }
+            self.detach(existing);
+        }
+        self.detach(source);
+        self.arena[source].name = name;
+        self.attach(source, dir);
+        true
+    }
+
+    // breadth first from the root, so every entry comes after its parent
+    fn reachable(&self) -> Vec<Inode> {
+        let mut order = vec![0];
+        let mut i = 0;
+        while i < order.len() {
+            order.extend(self.children[order[i]].values());
+            i += 1;
         }
+        order
+    }
+
+    fn sizes(&self) -> &[Option<usize>] {
+        self.sizes.get_or_init(|| {
+            let mut sizes = vec![None; self.arena.len()];
+            for inode in self.reachable().into_iter().rev() {
+                let entry = &self.arena[inode];
+                sizes[inode] = Some(match entry.entry_type {
+                    Type::File => entry.size,
+                    Type::Dir => self.children[inode]
+                        .values()
+                        .filter_map(|child| sizes[*child])
+                        .sum(),
+                });
+            }
+            sizes
+        })
+    }
+
+    fn du(&self, inode: Inode) -> usize {
+        self.sizes()[inode].unwrap_or(0)
+    }
+
+    // the size of every directory still reachable from the root
+    fn dir_sizes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.sizes()
+            .iter()
+            .zip(&self.arena)
+            .filter(|(_, entry)| entry.entry_type == Type::Dir)
+            .filter_map(|(size, _)| *size)
     }
 
     fn estimate_cleanup_space(&self) -> usize {
-        let mut result = 0;
-        for entry in self.arena.iter() {
-            let du = self.du(entry.address);
-            if du <= 100000 && entry.entry_type == Type::Dir {
-                result += du;
+        self.dir_sizes().filter(|size| *size <= 100000).sum()
+    }
+
+    /// How much has to be deleted to have `required` free on a disk of `disk`.
+    pub fn to_free(&self, disk: usize, required: usize) -> usize {
+        required.saturating_sub(disk.saturating_sub(self.du(0)))
+    }
+
+    fn candidate(&self, inode: Inode) -> Candidate {
+        Candidate {
+            path: self.path(inode),
+            size: self.du(inode),
+        }
+    }
+
+    /// The smallest directory that frees enough space, or `None` if there already is enough.
+    pub fn smallest_to_delete(&self, disk: usize, required: usize) -> Result<Option<Candidate>> {
+        let to_free = self.to_free(disk, required);
+        if to_free == 0 {
+            return Ok(None);
+        }
+        let smallest = self
+            .reachable()
+            .into_iter()
+            .filter(|inode| *inode != 0 && self.is_dir(*inode) && self.du(*inode) >= to_free)
+            .min_by_key(|inode| self.du(*inode));
+        match smallest {
+            Some(inode) => Ok(Some(self.candidate(inode))),
+            None => bail!("day 7: no directory frees {}", to_free),
+        }
+    }
+
+    /// The fewest directories that together free enough space, sorted by path, or none if
+    /// there already is enough.
+    pub fn fewest_to_delete(&self, disk: usize, required: usize) -> Result<Vec<Candidate>> {
+        let to_free = self.to_free(disk, required);
+        // any pick can be swapped for the top level directories holding it, which are no
+        // more in number and free at least as much, so take the biggest of those first
+        let mut dirs: Vec<Inode> = self.ls(0).values().copied().collect();
+        dirs.retain(|dir| self.is_dir(*dir));
+        dirs.sort_by_key(|dir| Reverse(self.du(*dir)));
+        let mut freed = 0;
+        let mut result = Vec::new();
+        for dir in dirs {
+            if freed >= to_free {
+                break;
             }
+            freed += self.du(dir);
+            result.push(self.candidate(dir));
+        }
+        if freed < to_free {
+            bail!("day 7: no set of directories frees {}", to_free);
+        }
+        result.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(result)
+    }
+
+    /// The largest directory other than the root no bigger than `threshold`.
+    pub fn largest_under(&self, threshold: usize) -> Option<Candidate> {
+        self.reachable()
+            .into_iter()
+            .filter(|inode| *inode != 0 && self.is_dir(*inode) && self.du(*inode) <= threshold)
+            .max_by_key(|inode| self.du(*inode))
+            .map(|inode| self.candidate(inode))
+    }
+
+    /// `tree`-style listing with the total size of every directory, entries sorted by name.
+    pub fn tree(&self) -> String {
+        let mut result = format!("/ (dir, {})", self.du(0));
+        // entries still to list, with the prefix of their line and whether they're last
+        let mut stack = Vec::new();
+        self.tree_children(0, "", &mut stack);
+        while let Some((child, prefix, last)) = stack.pop() {
+            let branch = if last { "└── " } else { "├── " };
+            let kind = child.entry_type.name();
+            let size = self.du(child.address);
+            result.push_str(&format!(
+                "\n{}{}{} ({}, {})",
+                prefix, branch, child.name, kind, size
+            ));
+            let indent = if last { "    " } else { "│   " };
+            self.tree_children(child.address, &format!("{}{}", prefix, indent), &mut stack);
         }
         result
     }
 
-    pub fn part2(&self) -> usize {
-        let total = 70000000;
-        let required = 30000000;
+    // pushed in reverse, so they're popped in order
+    fn tree_children<'a>(
+        &'a self,
+        dir: Inode,
+        prefix: &str,
+        stack: &mut Vec<(&'a Entry, String, bool)>,
+    ) {
+        let children = self.sorted_children(dir);
+        let count = children.len();
+        for (i, child) in children.into_iter().enumerate().rev() {
+            stack.push((child, prefix.to_owned(), i + 1 == count));
+        }
+    }
 
-	let used = self.du(0);
-        let unused = total - used;
-        let need_to_free = required - unused;
+    fn sorted_children(&self, dir: Inode) -> Vec<&Entry> {
+        let mut children: Vec<&Entry> = self.ls(dir).values().map(|i| &self.arena[*i]).collect();
+        children.sort_by(|a, b| a.name.cmp(&b.name));
+        children
+    }
 
-        self.arena
-            .iter()
-            .filter(|entry| entry.entry_type == Type::Dir)
-            .map(|entry| self.du(entry.address))
-            .filter(|size| size >= &need_to_free)
-            .min()
-            .unwrap()
+    /// The tree as JSON objects with `name`, `type` and `size`, and `children` for
+    /// directories.
+    pub fn to_json(&self) -> String {
+        let mut result = String::new();
+        let mut stack = vec![Json::Entry(0)];
+        while let Some(next) = stack.pop() {
+            let inode = match next {
+                Json::Entry(inode) => inode,
+                Json::Text(text) => {
+                    result.push_str(text);
+                    continue;
+                }
+            };
+            let entry = &self.arena[inode];
+            result.push_str(&format!(
+                "{{\"name\":{},\"type\":\"{}\",\"size\":{}",
+                json_string(&entry.name),
+                entry.entry_type.name(),
+                self.du(inode)
+            ));
+            if entry.entry_type == Type::File {
+                result.push('}');
+                continue;
+            }
+            result.push_str(",\"children\":[");
+            stack.push(Json::Text("]}"));
+            for (i, child) in self.sorted_children(inode).iter().enumerate().rev() {
+                stack.push(Json::Entry(child.address));
+                if i > 0 {
+                    stack.push(Json::Text(","));
+                }
+            }
+        }
+        result
     }
+
+    /// Recreates the tree inside `dir`, with sparse files of the listed sizes.
+    pub fn materialise(&self, dir: &Path) -> Result<()> {
+        fs::create_dir_all(dir)?;
+        // entries still to write, each with the directory it goes in
+        let mut stack: Vec<(Inode, PathBuf)> = Vec::new();
+        for child in self.sorted_children(0).iter().rev() {
+            stack.push((child.address, dir.to_owned()));
+        }
+        while let Some((inode, dir)) = stack.pop() {
+            if let Some(path) = self.write_entry(inode, &dir)? {
+                for child in self.sorted_children(inode).iter().rev() {
+                    stack.push((child.address, path.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Like `materialise`, into a new directory under the system's temp directory.
+    pub fn materialise_in_temp(&self) -> Result<PathBuf> {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+        let dir = env::temp_dir().join(format!("aoc-day7-{}-{}", process::id(), nanos));
+        if let Err(error) = self.materialise(&dir) {
+            let _ = fs::remove_dir_all(&dir);
+            return Err(error);
+        }
+        Ok(dir)
+    }
+
+    // the path of the new directory, for its children to go in
+    fn write_entry(&self, inode: Inode, dir: &Path) -> Result<Option<PathBuf>> {
+        let entry = &self.arena[inode];
+        if entry.name.is_empty()
+            || entry.name == "."
+            || entry.name == ".."
+            || entry.name.contains('/')
+        {
+            bail!("day 7: can't write {} to disk", self.path(inode));
+        }
+        let path = dir.join(&entry.name);
+        match entry.entry_type {
+            Type::File => {
+                File::create(path)?.set_len(entry.size as u64)?;
+                Ok(None)
+            }
+            Type::Dir => {
+                fs::create_dir(&path)?;
+                Ok(Some(path))
+            }
+        }
+    }
+}
+
+// what `to_json` still has to write: an entry, or the text closing or separating them
+enum Json {
+    Entry(Inode),
+    Text(&'static str),
+}
+
+fn json_string(s: &str) -> String {
+    let mut result = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            c if c.is_control() => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
 }
 
 struct Interpreter {
     fs: FileSystem,
+    line: usize,
 }
 
 impl Interpreter {
-    fn traverse(&mut self, input: &mut VecDeque<&str>) {
-        while !input.is_empty() {
-            let line = input.pop_front().unwrap();
+    fn traverse(&mut self, input: &mut VecDeque<&str>) -> Result<(), ParseError> {
+        while let Some(line) = input.pop_front() {
+            let mut scanner = Scanner::new(Day7::DAY, self.line, line);
+            self.line += 1;
             if line.starts_with('$') {
-                let mut tokens = line.split(' ');
-                let cmd = tokens.nth(1).unwrap();
-                if cmd == "cd" {
-                    let target_dir = tokens.next().unwrap();
-                    self.fs.cd(target_dir);
-                } else if cmd == "ls" {
-                    self.tranverse_ls(input);
-                }
+                scanner.tag("$ ")?;
+                self.command(&mut scanner, input)?;
             }
         }
+        Ok(())
     }
 
-    fn tranverse_ls(&mut self, input: &mut VecDeque<&str>) {
-        while !input.is_empty() {
-            let head = input.pop_front();
-            if let Some(fr) = head {
-                if !fr.starts_with('$') {
-                    self.track_child(fr);
+    fn command(
+        &mut self,
+        scanner: &mut Scanner,
+        input: &mut VecDeque<&str>,
+    ) -> Result<(), ParseError> {
+        let at_cmd = scanner.clone();
+        let cmd = scanner.until(' ');
+        let ok = match cmd {
+            "ls" | "pwd" => {
+                scanner.end()?;
+                if cmd == "ls" {
+                    self.tranverse_ls(input)?;
+                }
+                return Ok(());
+            }
+            "cd" => {
+                let (at, path) = argument(scanner)?;
+                self.fs
+                    .cd(path)
+                    .then_some(())
+                    .ok_or(at.error("a directory"))
+            }
+            "mkdir" => {
+                let (at, path) = argument(scanner)?;
+                let created = self.fs.mkdir(path);
+                created.map(|_| ()).ok_or(at.error("a new directory"))
+            }
+            "touch" => {
+                let (at, path) = argument(scanner)?;
+                let created = self.fs.touch(path);
+                created.map(|_| ()).ok_or(at.error("a file path"))
+            }
+            "rm" => {
+                let recursive = scanner.rest().starts_with(" -r ");
+                if recursive {
+                    scanner.tag(" -r")?;
+                }
+                let (at, path) = argument(scanner)?;
+                let expected = if recursive { "a path" } else { "a file" };
+                let removed = self.fs.rm(path, recursive);
+                removed.then_some(()).ok_or(at.error(expected))
+            }
+            "mv" => {
+                let (at_from, from) = argument(scanner)?;
+                let (at_to, to) = argument(scanner)?;
+                if self.fs.resolve(from).is_none() {
+                    Err(at_from.error("an existing path"))
                 } else {
-                    input.push_front(fr);
-                    break;
+                    let moved = self.fs.mv(from, to);
+                    moved.then_some(()).ok_or(at_to.error("a destination"))
                 }
             }
-        }
+            _ => Err(at_cmd.error("a command")),
+        };
+        ok?;
+        scanner.end()
     }
 
-    fn track_child(&mut self, line: &str) {
-        let mut tokens = line.split(' ');
-        let size = tokens.next().unwrap();
-        let name = tokens.next().unwrap();
-        self.create_child(name, size);
-    }
-
-    fn create_child(&mut self, name: &str, size_or_dir_flag: &str) -> Inode {
-        let children = self.fs.ls_cwd();
-        if size_or_dir_flag == "dir" {
-            if children.contains_key(name) {
-                return children.get(name).unwrap().address;
+    fn tranverse_ls(&mut self, input: &mut VecDeque<&str>) -> Result<(), ParseError> {
+        while let Some(fr) = input.pop_front() {
+            if !fr.starts_with('$') {
+                self.track_child(fr)?;
+                self.line += 1;
             } else {
-                return self.fs.mkdir(name);
+                input.push_front(fr);
+                break;
             }
         }
-        if children.contains_key(name) {
-            children.get(name).unwrap().address
-        } else {
-            let size = size_or_dir_flag
-                .parse()
-                .unwrap_or_else(|_| panic!("create_child: failed to parse {}", size_or_dir_flag));
-            self.fs.touch(name, size)
+        Ok(())
+    }
+
+    fn track_child(&mut self, line: &str) -> Result<Inode, ParseError> {
+        let mut scanner = Scanner::new(Day7::DAY, self.line, line);
+        let cwd = self.fs.cwd;
+        if line.starts_with("dir ") {
+            scanner.tag("dir ")?;
+            let name = scanner.rest();
+            return Ok(self.fs.create(cwd, name, None));
         }
+        let size = scanner.number()?;
+        scanner.tag(" ")?;
+        let name = scanner.rest();
+        Ok(self.fs.create(cwd, name, Some(size)))
     }
+}
 
-    fn estimate_cleanup_space(&self) -> usize {
-        self.fs.estimate_cleanup_space()
+// a space and the word after it, with where it starts for error reporting
+fn argument<'a>(scanner: &mut Scanner<'a>) -> Result<(Scanner<'a>, &'a str), ParseError> {
+    scanner.tag(" ")?;
+    let at = scanner.clone();
+    let word = scanner.until(' ');
+    if word.is_empty() {
+        return Err(at.error("a path"));
+    }
+    Ok((at, word))
+}
+
+pub struct Day7;
+
+impl Solution for Day7 {
+    const YEAR: u32 = 2022;
+    const DAY: u32 = 7;
+
+    type Input = FileSystem;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let input: Vec<&str> = input.lines().collect();
+        Ok(replay(&input)?)
     }
 
-    fn part2(&self) -> usize {
-        self.fs.part2()
+    fn part1(fs: &Self::Input) -> Result<usize> {
+        Ok(fs.estimate_cleanup_space())
+    }
+
+    fn part2(fs: &Self::Input) -> Result<usize> {
+        let dir = fs.smallest_to_delete(DISK, REQUIRED)?;
+        Ok(dir.map_or(0, |dir| dir.size))
     }
 }
 
@@ -231,6 +675,33 @@ mod tests {
     use super::*;
     use crate::util;
 
+    // the puzzle's example, with b.txt as big as the puzzle has it so the cleanup adds up
+    const CLEANUP_EXAMPLE: [&str; 23] = [
+        "cd /",
+        "$ ls",
+        "dir a",
+        "14848514 b.txt",
+        "8504156 c.dat",
+        "dir d",
+        "$ cd a",
+        "$ ls",
+        "dir e",
+        "29116 f",
+        "2557 g",
+        "62596 h.lst",
+        "$ cd e",
+        "$ ls",
+        "584 i",
+        "$ cd ..",
+        "$ cd ..",
+        "$ cd d",
+        "$ ls",
+        "4060174 j",
+        "8033020 d.log",
+        "5626152 d.ext",
+        "7214296 k",
+    ];
+
     #[test]
     fn test_solve() {
         let input = vec![
@@ -258,7 +729,218 @@ mod tests {
             "5626152 d.ext",
             "7214296 k",
         ];
-        assert_eq!(solve(input.into()), 95437);
+        assert_eq!(solve(input.into()).unwrap(), 95437);
+    }
+
+    #[test]
+    fn test_cleanup() {
+        let fs = replay(&CLEANUP_EXAMPLE).unwrap();
+        let d = Candidate {
+            path: "/d".to_owned(),
+            size: 24933642,
+        };
+        assert_eq!(fs.to_free(DISK, REQUIRED), 8381165);
+        assert_eq!(
+            fs.smallest_to_delete(DISK, REQUIRED).unwrap(),
+            Some(d.clone())
+        );
+        assert_eq!(
+            fs.fewest_to_delete(DISK, REQUIRED).unwrap(),
+            vec![d.clone()]
+        );
+
+        // d and one byte more takes a as well
+        let required = 46552478;
+        let error = fs.smallest_to_delete(DISK, required).unwrap_err();
+        assert_eq!(error.to_string(), "day 7: no directory frees 24933643");
+        let fewest = fs.fewest_to_delete(DISK, required).unwrap();
+        assert_eq!(fewest.iter().map(|dir| dir.size).sum::<usize>(), 25028495);
+        assert_eq!(fewest[1], d);
+        let error = fs.fewest_to_delete(DISK, required + 94853).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 7: no set of directories frees 25028496"
+        );
+
+        assert_eq!(fs.largest_under(100000).unwrap().path, "/a");
+        assert_eq!(fs.largest_under(1000).unwrap().path, "/a/e");
+        assert_eq!(fs.largest_under(100), None);
+    }
+
+    #[test]
+    fn test_enough_space() {
+        let fs = replay(&CLEANUP_EXAMPLE).unwrap();
+        assert_eq!(fs.to_free(DISK, 10000000), 0);
+        assert_eq!(fs.smallest_to_delete(DISK, 10000000).unwrap(), None);
+        assert!(fs.fewest_to_delete(DISK, 10000000).unwrap().is_empty());
+        // a disk smaller than what's on it
+        assert_eq!(fs.to_free(100, 5), 5);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let input = vec!["$ cd /", "$ ls", "dir a", "14848x54 b.txt"];
+        let error = solve(input.into()).unwrap_err();
+        assert_eq!(
+            error.downcast::<ParseError>().unwrap(),
+            ParseError::new(7, 4, 6, "\" \"", "x54")
+        );
+
+        let input = vec!["$ cd /", "$ cd ..", "$ cd .."];
+        let error = solve(input.into()).unwrap_err();
+        assert_eq!(
+            error.downcast::<ParseError>().unwrap(),
+            ParseError::new(7, 2, 6, "a directory", "..")
+        );
+
+        let input = vec!["$ cd /", "$ cat a"];
+        let error = solve(input.into()).unwrap_err();
+        assert_eq!(
+            error.downcast::<ParseError>().unwrap(),
+            ParseError::new(7, 2, 3, "a command", "cat")
+        );
+
+        let error = replay(&["$ mkdir a", "$ rm a"]).err().unwrap();
+        assert_eq!(error, ParseError::new(7, 2, 6, "a file", "a"));
+
+        let error = replay(&["$ mkdir a", "$ mv b a"]).err().unwrap();
+        assert_eq!(error, ParseError::new(7, 2, 6, "an existing path", "b"));
+
+        let error = replay(&["$ mkdir a/b"]).err().unwrap();
+        assert_eq!(error, ParseError::new(7, 1, 9, "a new directory", "a/b"));
+    }
+
+    #[test]
+    fn test_commands() {
+        let mut fs = replay(&[
+            "$ mkdir /a",
+            "$ mkdir a/b",
+            "$ touch a/b/c",
+            "$ cd a/b",
+            "$ pwd",
+            "/a/b",
+            "$ ls",
+            "10 d",
+        ])
+        .unwrap();
+        assert_eq!(fs.pwd(), "/a/b");
+        assert!(fs.cd("../.."));
+        assert_eq!(fs.pwd(), "/");
+        assert!(!fs.cd("a/b/c"));
+
+        assert!(fs.mv("a/b/d", "/e"));
+        assert!(fs.mv("/a/b", "."));
+        assert!(!fs.mv("/", "b"));
+        assert!(!fs.mv("a", "a/x"));
+        assert!(!fs.mv("a", "e"));
+        assert!(fs.cd("b"));
+        assert!(!fs.rm("/b", true));
+        assert!(fs.cd("/"));
+        assert!(fs.rm("b", true));
+        assert_eq!(fs.tree(), "/ (dir, 10)\n├── a (dir, 0)\n└── e (file, 10)");
+    }
+
+    #[test]
+    fn test_sizes_follow_changes() {
+        let mut fs = replay(&["$ ls", "dir a", "5 b", "$ cd a", "$ ls", "7 c"]).unwrap();
+        assert_eq!(fs.estimate_cleanup_space(), 19);
+        assert!(fs.mv("c", "/"));
+        assert_eq!(fs.estimate_cleanup_space(), 12);
+        assert!(fs.rm("/b", false));
+        assert_eq!(fs.du(0), 7);
+    }
+
+    #[test]
+    fn test_deep_tree() {
+        let mut input = Vec::new();
+        for _ in 0..100_000 {
+            input.extend(["$ ls", "dir a", "1 b", "$ cd a"]);
+        }
+        let fs = replay(&input).unwrap();
+        assert_eq!(fs.du(0), 100_000);
+        assert_eq!(fs.estimate_cleanup_space(), (1..=100_000).sum());
+        let json = fs.to_json();
+        assert_eq!(json.matches("\"children\"").count(), 100_001);
+        assert_eq!(json.matches("]}").count(), 100_001);
+
+        // every line of `tree` repeats the indent of the ones above, so it's kept shallower
+        let mut input = Vec::new();
+        for _ in 0..10_000 {
+            input.extend(["$ ls", "dir a", "$ cd a"]);
+        }
+        let tree = replay(&input).unwrap().tree();
+        assert_eq!(tree.lines().count(), 10_001);
+        let last = format!("{}└── a (dir, 0)", "    ".repeat(9_999));
+        assert_eq!(tree.lines().last(), Some(last.as_str()));
+    }
+
+    #[test]
+    fn test_json() {
+        let fs = replay(&["$ ls", "dir a", "5 b\"c", "$ cd a", "$ ls", "7 d"]).unwrap();
+        let expected = concat!(
+            r#"{"name":"/","type":"dir","size":12,"children":["#,
+            r#"{"name":"a","type":"dir","size":7,"children":["#,
+            r#"{"name":"d","type":"file","size":7}]},"#,
+            r#"{"name":"b\"c","type":"file","size":5}]}"#,
+        );
+        assert_eq!(fs.to_json(), expected);
+    }
+
+    #[test]
+    fn test_materialise() {
+        // removes the directory even when an assertion fails
+        struct TempDir(PathBuf);
+
+        impl Drop for TempDir {
+            fn drop(&mut self) {
+                let _ = std::fs::remove_dir_all(&self.0);
+            }
+        }
+
+        let fs = replay(&CLEANUP_EXAMPLE).unwrap();
+        let dir = TempDir(fs.materialise_in_temp().unwrap());
+        let size = |path: &str| std::fs::metadata(dir.0.join(path)).unwrap().len();
+        assert_eq!(size("b.txt"), 14848514);
+        assert_eq!(size("a/e/i"), 584);
+        assert_eq!(size("d/k"), 7214296);
+        assert!(dir.0.join("a/e").is_dir());
+
+        let fs = replay(&["$ ls", "dir .."]).unwrap();
+        let error = fs.materialise_in_temp().unwrap_err();
+        assert_eq!(error.to_string(), "day 7: can't write /.. to disk");
+    }
+
+    #[test]
+    fn test_tree() {
+        let fs = replay(&[
+            "$ cd /",
+            "$ ls",
+            "dir a",
+            "14848514 b.txt",
+            "dir d",
+            "$ cd a",
+            "$ ls",
+            "dir e",
+            "29116 f",
+            "$ cd e",
+            "$ ls",
+            "584 i",
+            "$ cd /d",
+            "$ ls",
+            "4060174 j",
+        ])
+        .unwrap();
+        let expected = [
+            "/ (dir, 18938388)",
+            "├── a (dir, 29700)",
+            "│   ├── e (dir, 584)",
+            "│   │   └── i (file, 584)",
+            "│   └── f (file, 29116)",
+            "├── b.txt (file, 14848514)",
+            "└── d (dir, 4060174)",
+            "    └── j (file, 4060174)",
+        ];
+        assert_eq!(fs.tree(), expected.join("\n"));
     }
 
     #[test]
@@ -266,7 +948,7 @@ mod tests {
         let data = util::read_real_data("day7");
         let data = data.iter().map(|line| line.as_str()).collect();
 
-        assert_eq!(part2(data), 2940614);
+        assert_eq!(part2(data).unwrap(), 2940614);
     }
 
     #[test]
@@ -274,6 +956,6 @@ mod tests {
         let data = util::read_real_data("day7");
         let data = data.iter().map(|line| line.as_str()).collect();
 
-        assert_eq!(solve(data), 1427048);
+        assert_eq!(solve(data).unwrap(), 1427048);
     }
 }
@@ -1,105 +1,116 @@
-pub fn solve(input: &[&str]) -> u32 {
-    let forest: Vec<Vec<u32>> = input.iter().map(|line| parse_line(line)).collect();
+use std::cmp::Reverse;
 
-    let height = forest.len();
-    let width = forest[0].len();
-    let mut result = 0;
-    for row in 0..height {
-        for col in 0..width {
-            if row == 0 || col == 0 || row == width - 1 || col == height - 1 {
-                result += 1;
-                continue;
-            }
-            result += check((row, col), &forest)
-        }
-    }
-    result
+use anyhow::Result;
+
+use crate::error::ParseError;
+use crate::grid::{Grid, Pos};
+use crate::solution::Solution;
+
+pub fn solve(input: &[&str]) -> Result<u32> {
+    Day8::part1(&parse(input)?)
 }
 
-fn check((row, col): (usize, usize), forest: &Vec<Vec<u32>>) -> u32 {
-    let height = forest.len();
-    let width = forest[0].len();
-
-    let horizontal = &forest[row];
-    let vertical: Vec<u32> = forest.iter().map(|line| line[col]).collect();
-
-    let slices = [
-        &horizontal[0..col],
-        &horizontal[col + 1..width],
-        &vertical[0..row],
-        &vertical[row + 1..height],
-    ];
-    let tree = forest[row][col];
-    for slice in slices {
-        if slice.iter().all(|t| t < &tree) {
-            return 1;
-        }
+pub fn part2(input: &[&str]) -> Result<u32> {
+    Day8::part2(&parse(input)?)
+}
+
+pub fn parse(input: &[&str]) -> Result<Grid<u32>, ParseError> {
+    if input.first().is_none_or(|line| line.is_empty()) {
+        return Err(ParseError::new(
+            Day8::DAY,
+            1,
+            1,
+            "a tree height",
+            "end of line",
+        ));
     }
-    0
+    Grid::parse(Day8::DAY, input, "a tree height", |c| c.to_digit(10))
 }
 
-fn parse_line(line: &str) -> Vec<u32> {
-    line.chars().map(|c| c as u32 - 48).collect()
+/// Whether each tree can be seen from outside the forest.
+pub fn visibility(forest: &Grid<u32>) -> Grid<bool> {
+    sweep_all(forest).0
 }
 
-pub fn part2(input: &[&str]) -> u32 {
-    let forest: Vec<Vec<u32>> = input.iter().map(|line| parse_line(line)).collect();
+/// How many trees each tree sees up, down, left and right, multiplied together.
+pub fn scenic_scores(forest: &Grid<u32>) -> Grid<u32> {
+    sweep_all(forest).1
+}
+
+/// The `k` highest scores, best first.
+pub fn best_trees(scores: &Grid<u32>, k: usize) -> Vec<(Pos, u32)> {
+    let mut trees: Vec<(Pos, u32)> = scores.iter().map(|(pos, score)| (pos, *score)).collect();
+    trees.sort_by_key(|(_, score)| Reverse(*score));
+    trees.truncate(k);
+    trees
+}
 
-    let height = forest.len();
-    let width = forest[0].len();
-    let mut result = 0;
+fn sweep_all(forest: &Grid<u32>) -> (Grid<bool>, Grid<u32>) {
+    let (width, height) = (forest.width() as i32, forest.height() as i32);
+    let mut visible = Grid::new(forest.width(), forest.height(), false);
+    let mut scores = Grid::new(forest.width(), forest.height(), 1);
     for row in 0..height {
-        for col in 0..width {
-            let score = score((row, col), &forest);
-            if score > result {
-                result = score;
-            }
-        }
+        let line: Vec<Pos> = (0..width).map(|col| Pos::new(col, row)).collect();
+        sweep(forest, line.iter(), &mut visible, &mut scores);
+        sweep(forest, line.iter().rev(), &mut visible, &mut scores);
     }
-    result
+    for col in 0..width {
+        let line: Vec<Pos> = (0..height).map(|row| Pos::new(col, row)).collect();
+        sweep(forest, line.iter(), &mut visible, &mut scores);
+        sweep(forest, line.iter().rev(), &mut visible, &mut scores);
+    }
+    (visible, scores)
 }
 
-fn score((row, col): (usize, usize), forest: &Vec<Vec<u32>>) -> u32 {
-    let height = forest.len();
-    let width = forest[0].len();
-
-    if row == 0 || col == 0 || row == width - 1 || col == height - 1 {
-        return 0;
+// looks back along `line` from every tree, keeping the trees that could still block the view
+// of a later one: each strictly shorter than the one before it
+fn sweep<'a>(
+    forest: &Grid<u32>,
+    line: impl Iterator<Item = &'a Pos>,
+    visible: &mut Grid<bool>,
+    scores: &mut Grid<u32>,
+) {
+    let mut blockers: Vec<(usize, u32)> = Vec::new();
+    for (i, pos) in line.enumerate() {
+        let tree = forest[*pos];
+        while blockers.last().is_some_and(|(_, height)| *height < tree) {
+            blockers.pop();
+        }
+        match blockers.last() {
+            Some((blocker, _)) => scores[*pos] *= (i - blocker) as u32,
+            None => {
+                visible[*pos] = true;
+                scores[*pos] *= i as u32;
+            }
+        }
+        blockers.push((i, tree));
     }
+}
 
-    let horizontal = &forest[row];
-    let vertical: Vec<u32> = forest.iter().map(|line| line[col]).collect();
-
-    let up = vertical[0..row].iter().rev().copied().collect();
-    let down = vertical[row + 1..height].to_vec();
-    let left = horizontal[0..col].iter().rev().copied().collect();
-    let right = horizontal[col + 1..width].to_vec();
-
-    let tree = forest[row][col];
+pub struct Day8;
 
-    let score_up = score_line(&up, tree);
-    let score_down = score_line(&down, tree);
-    let score_left = score_line(&left, tree);
-    let score_right = score_line(&right, tree);
+impl Solution for Day8 {
+    const YEAR: u32 = 2022;
+    const DAY: u32 = 8;
 
-    score_down * score_up * score_left * score_right
-}
+    type Input = Grid<u32>;
+    type Part1 = u32;
+    type Part2 = u32;
 
-fn score_line(line: &Vec<u32>, tree: u32) -> u32 {
-    if line.len() == 1 {
-        return 1;
+    fn parse(input: &str) -> Result<Self::Input> {
+        let input: Vec<&str> = input.lines().collect();
+        Ok(parse(&input)?)
     }
 
-    let mut result = 1;
-    for i in 0..line.len() - 1 {
-        if line[i] <= line[i + 1] && line[i] < tree {
-            result += 1;
-        } else {
-            return result;
-        }
+    fn part1(forest: &Self::Input) -> Result<u32> {
+        let visible = visibility(forest);
+        Ok(visible.iter().filter(|(_, visible)| **visible).count() as u32)
     }
 
-    result
+    fn part2(forest: &Self::Input) -> Result<u32> {
+        let scores = scenic_scores(forest);
+        Ok(scores.iter().map(|(_, score)| *score).max().unwrap_or(0))
+    }
 }
 
 #[cfg(test)]
@@ -108,39 +119,62 @@ mod tests {
     use crate::util;
 
     #[test]
-    fn test_score_line() {
-        assert_eq!(score_line(&vec![3], 5), 1);
-        assert_eq!(score_line(&vec![3, 3], 5), 2);
-        assert_eq!(score_line(&vec![5, 2], 5), 1);
-        assert_eq!(score_line(&vec![1, 2], 5), 2);
-        assert_eq!(score_line(&vec![3, 5, 3], 5), 2);
-
-        assert_eq!(score_line(&vec![3, 5, 6], 3), 1);
+    fn test_solve() {
+        let input = vec!["30373", "25512", "65332", "33549", "35390"];
+        assert_eq!(solve(&input).unwrap(), 21);
     }
 
     #[test]
-    fn test_solve() {
-        let input = vec!["30373", "25512", "65332", "33549", "35390"];
-        assert_eq!(solve(&input), 21);
+    fn test_parse_error() {
+        let input = vec!["30373", "2551", "65332"];
+        let error = solve(&input).unwrap_err();
+        assert_eq!(
+            error.downcast::<ParseError>().unwrap(),
+            ParseError::new(8, 2, 5, "a tree height", "end of line")
+        );
     }
 
     #[test]
     fn test_with_real_data() {
         let input = util::read_real_data("day8");
         let input: Vec<&str> = input.iter().map(|line| line.as_str()).collect();
-        assert_eq!(solve(&input), 1832);
+        assert_eq!(solve(&input).unwrap(), 1832);
     }
 
     #[test]
     fn test_part2() {
         let input = vec!["30373", "25512", "65332", "33549", "35390"];
-        assert_eq!(part2(&input), 8);
+        assert_eq!(part2(&input).unwrap(), 8);
+    }
+
+    #[test]
+    fn test_masks() {
+        let input = vec!["30373", "25512", "65332", "33549", "35390"];
+        let forest = parse(&input).unwrap();
+        let visible = visibility(&forest).map(|visible| if *visible { '#' } else { '.' });
+        let expected = ["#####", "###.#", "##.##", "#.#.#", "#####"];
+        assert_eq!(visible.to_string(), expected.join("\n"));
+
+        let scores = scenic_scores(&forest);
+        assert_eq!(scores[Pos::new(2, 1)], 4);
+        assert_eq!(scores[Pos::new(0, 2)], 0);
+        assert_eq!(
+            best_trees(&scores, 2),
+            vec![(Pos::new(2, 3), 8), (Pos::new(1, 2), 6)]
+        );
+    }
+
+    #[test]
+    fn test_scenic_score_past_shorter_trees() {
+        // looking left, 5 sees 3 and the shorter 1 behind it before 9 blocks the view
+        let forest = parse(&["99999", "91359", "99999"]).unwrap();
+        assert_eq!(scenic_scores(&forest)[Pos::new(3, 1)], 3);
     }
 
     #[test]
     fn test_part2_with_real_data() {
         let input = util::read_real_data("day8");
         let input: Vec<&str> = input.iter().map(|line| line.as_str()).collect();
-        assert_eq!(part2(&input), 192);
+        assert_eq!(part2(&input).unwrap(), 157320);
     }
 }
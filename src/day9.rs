@@ -1,5 +1,10 @@
 use std::collections::HashSet;
 
+use anyhow::Result;
+
+use crate::error::{ParseError, Scanner};
+use crate::solution::Solution;
+
 fn move_tail(head: (i32, i32), tail: (i32, i32)) -> (i32, i32) {
     if head == tail {
         return tail;
@@ -42,21 +47,27 @@ fn is_adjacent(head: (i32, i32), tail: (i32, i32)) -> bool {
     around.contains(&tail)
 }
 
-fn parse_head_moves(input: &Vec<&str>) -> String {
+fn parse_head_moves(input: &[&str]) -> Result<String, ParseError> {
     let mut result = String::from("");
-    for line in input {
-        let mut tokens = line.split(' ');
-        let direction = tokens.next().unwrap().chars().next().unwrap();
-        let count = tokens.next().unwrap().parse().unwrap();
+    for (i, line) in input.iter().enumerate() {
+        let mut scanner = Scanner::new(Day9::DAY, i, line);
+        let direction =
+            scanner.char_map("R, U, L or D", |c| Some(c).filter(|c| "RULD".contains(*c)))?;
+        scanner.tag(" ")?;
+        let count: usize = scanner.number()?;
+        scanner.end()?;
         for _ in 0..count {
             result.push(direction);
         }
     }
-    result
+    Ok(result)
 }
 
-pub fn solve(input: &Vec<&str>) -> usize {
-    let moves = parse_head_moves(input);
+pub fn solve(input: &Vec<&str>) -> Result<usize> {
+    Ok(tail_positions(&parse_head_moves(input)?))
+}
+
+fn tail_positions(moves: &str) -> usize {
     let mut head = (0, 0);
     let mut tail = (0, 0);
     let mut result = HashSet::new();
@@ -68,8 +79,11 @@ pub fn solve(input: &Vec<&str>) -> usize {
     result.len()
 }
 
-pub fn part2(input: &Vec<&str>) -> usize {
-    let moves = parse_head_moves(input);
+pub fn part2(input: &Vec<&str>) -> Result<usize> {
+    Ok(last_knot_positions(&parse_head_moves(input)?))
+}
+
+fn last_knot_positions(moves: &str) -> usize {
     let mut rope = [(0, 0); 10];
     let mut result = HashSet::new();
     for move_ in moves.chars() {
@@ -92,6 +106,30 @@ fn move_head((x, y): (i32, i32), move_: char) -> (i32, i32) {
     }
 }
 
+pub struct Day9;
+
+impl Solution for Day9 {
+    const YEAR: u32 = 2022;
+    const DAY: u32 = 9;
+
+    type Input = String;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let input: Vec<&str> = input.lines().collect();
+        Ok(parse_head_moves(&input)?)
+    }
+
+    fn part1(moves: &Self::Input) -> Result<usize> {
+        Ok(tail_positions(moves))
+    }
+
+    fn part2(moves: &Self::Input) -> Result<usize> {
+        Ok(last_knot_positions(moves))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -110,13 +148,22 @@ mod tests {
     #[test]
     fn test_parse_head_moves() {
         let input = vec!["R 4", "U 4", "L 3", "D 1", "R 4", "D 1", "L 5", "R 2"];
-        assert_eq!(parse_head_moves(&input), "RRRRUUUULLLDRRRRDLLLLLRR");
+        assert_eq!(
+            parse_head_moves(&input).unwrap(),
+            "RRRRUUUULLLDRRRRDLLLLLRR"
+        );
+    }
+
+    #[test]
+    fn test_parse_head_moves_error() {
+        let error = parse_head_moves(&["R 4", "X 4"]).unwrap_err();
+        assert_eq!(error, ParseError::new(9, 2, 1, "R, U, L or D", "X"));
     }
 
     #[test]
     fn test_solve() {
         let input = vec!["R 4", "U 4", "L 3", "D 1", "R 4", "D 1", "L 5", "R 2"];
-        assert_eq!(solve(&input), 13);
+        assert_eq!(solve(&input).unwrap(), 13);
     }
 
     #[test]
@@ -124,13 +171,13 @@ mod tests {
         let input = util::read_real_data("day9");
         let input = input.iter().map(|line| line.as_str()).collect();
 
-        assert_eq!(solve(&input), 6486);
+        assert_eq!(solve(&input).unwrap(), 6486);
     }
 
     #[test]
     fn test_part2() {
         let input = vec!["R 5", "U 8", "L 8", "D 3", "R 17", "D 10", "L 25", "U 20"];
-        assert_eq!(part2(&input), 36);
+        assert_eq!(part2(&input).unwrap(), 36);
     }
 
     #[test]
@@ -138,6 +185,6 @@ mod tests {
         let input = util::read_real_data("day9");
         let input = input.iter().map(|line| line.as_str()).collect();
 
-        assert_eq!(part2(&input), 2678);
+        assert_eq!(part2(&input).unwrap(), 2678);
     }
 }
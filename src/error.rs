@@ -0,0 +1,194 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Malformed puzzle input: where it was found and what was expected there.
+/// Lines and columns are 1-based, columns count chars.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: u32,
+    pub line: usize,
+    pub column: usize,
+    pub expected: String,
+    pub found: String,
+}
+
+impl ParseError {
+    pub fn new(day: u32, line: usize, column: usize, expected: &str, found: &str) -> Self {
+        Self {
+            day,
+            line,
+            column,
+            expected: expected.to_owned(),
+            found: found.to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "day {}: line {}, column {}: expected {}, found {:?}",
+            self.day, self.line, self.column, self.expected, self.found
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+/// Cursor over one input line that remembers its position for error reporting.
+#[derive(Clone)]
+pub struct Scanner<'a> {
+    day: u32,
+    line: usize,
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    /// `line` is the 0-based index of `text` in the input.
+    pub fn new(day: u32, line: usize, text: &'a str) -> Self {
+        Self {
+            day,
+            line: line + 1,
+            text,
+            pos: 0,
+        }
+    }
+
+    pub fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos == self.text.len()
+    }
+
+    pub fn column(&self) -> usize {
+        self.text[..self.pos].chars().count() + 1
+    }
+
+    /// Error at the current position, reporting the next whitespace-separated token as found.
+    pub fn error(&self, expected: &str) -> ParseError {
+        let found = self
+            .rest()
+            .split_whitespace()
+            .next()
+            .unwrap_or("end of line");
+        ParseError::new(self.day, self.line, self.column(), expected, found)
+    }
+
+    pub fn tag(&mut self, tag: &str) -> Result<(), ParseError> {
+        if !self.rest().starts_with(tag) {
+            return Err(self.error(&format!("{:?}", tag)));
+        }
+        self.pos += tag.len();
+        Ok(())
+    }
+
+    pub fn char(&mut self) -> Result<char, ParseError> {
+        let c = self
+            .rest()
+            .chars()
+            .next()
+            .ok_or_else(|| self.error("a character"))?;
+        self.pos += c.len_utf8();
+        Ok(c)
+    }
+
+    /// Next char, accepted only if `convert` recognises it.
+    pub fn char_map<T>(
+        &mut self,
+        expected: &str,
+        convert: impl FnOnce(char) -> Option<T>,
+    ) -> Result<T, ParseError> {
+        let c = self.rest().chars().next();
+        let result = c.and_then(convert).ok_or_else(|| self.error(expected))?;
+        self.pos += c.map_or(0, char::len_utf8);
+        Ok(result)
+    }
+
+    /// Optionally signed decimal number.
+    pub fn number<T: FromStr>(&mut self) -> Result<T, ParseError> {
+        let rest = self.rest();
+        let sign = usize::from(rest.starts_with('-'));
+        let digits = rest[sign..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len() - sign);
+        let result = rest[..sign + digits]
+            .parse()
+            .map_err(|_| self.error("a number"))?;
+        self.pos += sign + digits;
+        Ok(result)
+    }
+
+    /// Everything up to (not including) `stop` or the end of line.
+    pub fn until(&mut self, stop: char) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(stop).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    /// Skips spaces and tabs, returning whether there were any.
+    pub fn whitespace(&mut self) -> bool {
+        let rest = self.rest();
+        let len = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+        self.pos += len;
+        len > 0
+    }
+
+    pub fn end(&self) -> Result<(), ParseError> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self.error("end of line"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scanner() {
+        let mut scanner = Scanner::new(5, 0, "move 12 from -3");
+        scanner.tag("move ").unwrap();
+        assert_eq!(scanner.number::<u32>(), Ok(12));
+        scanner.tag(" from ").unwrap();
+        assert_eq!(scanner.number::<i32>(), Ok(-3));
+        assert!(scanner.end().is_ok());
+    }
+
+    #[test]
+    fn test_scanner_error() {
+        let mut scanner = Scanner::new(5, 2, "move x from 3");
+        scanner.tag("move ").unwrap();
+        let error = scanner.number::<u32>().unwrap_err();
+        assert_eq!(error, ParseError::new(5, 3, 6, "a number", "x"));
+        assert_eq!(
+            error.to_string(),
+            "day 5: line 3, column 6: expected a number, found \"x\""
+        );
+    }
+
+    #[test]
+    fn test_scanner_end() {
+        let mut scanner = Scanner::new(1, 0, "12 ");
+        assert_eq!(scanner.number::<u32>(), Ok(12));
+        assert_eq!(scanner.end().unwrap_err().column, 3);
+        assert_eq!(scanner.tag(",").unwrap_err().found, "end of line");
+    }
+
+    #[test]
+    fn test_scanner_whitespace() {
+        let mut scanner = Scanner::new(5, 0, "move \t 1");
+        assert!(!scanner.whitespace());
+        scanner.tag("move").unwrap();
+        assert!(scanner.whitespace());
+        assert_eq!(scanner.column(), 8);
+        assert_eq!(scanner.number::<u32>(), Ok(1));
+    }
+}
@@ -0,0 +1,412 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Add, Index, IndexMut, Sub};
+
+use crate::error::{ParseError, Scanner};
+
+/// A cell position, columns grow to the right and rows grow downwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Pos {
+    pub col: i32,
+    pub row: i32,
+}
+
+impl Pos {
+    pub const UP: Pos = Pos::new(0, -1);
+    pub const DOWN: Pos = Pos::new(0, 1);
+    pub const LEFT: Pos = Pos::new(-1, 0);
+    pub const RIGHT: Pos = Pos::new(1, 0);
+
+    pub const fn new(col: i32, row: i32) -> Self {
+        Self { col, row }
+    }
+
+    /// Up, right, down, left.
+    pub fn neighbours4(self) -> [Pos; 4] {
+        [Pos::UP, Pos::RIGHT, Pos::DOWN, Pos::LEFT].map(|offset| self + offset)
+    }
+
+    /// Clockwise from the top left corner.
+    pub fn neighbours8(self) -> [Pos; 8] {
+        [
+            Pos::new(-1, -1),
+            Pos::new(0, -1),
+            Pos::new(1, -1),
+            Pos::new(1, 0),
+            Pos::new(1, 1),
+            Pos::new(0, 1),
+            Pos::new(-1, 1),
+            Pos::new(-1, 0),
+        ]
+        .map(|offset| self + offset)
+    }
+
+    pub fn manhattan(self, other: Pos) -> u32 {
+        self.col.abs_diff(other.col) + self.row.abs_diff(other.row)
+    }
+}
+
+impl Add for Pos {
+    type Output = Pos;
+
+    fn add(self, rhs: Pos) -> Pos {
+        Pos::new(self.col + rhs.col, self.row + rhs.row)
+    }
+}
+
+impl Sub for Pos {
+    type Output = Pos;
+
+    fn sub(self, rhs: Pos) -> Pos {
+        Pos::new(self.col - rhs.col, self.row - rhs.row)
+    }
+}
+
+/// Dense rectangular grid, stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    /// One input line per row, every row must be as wide as the first one.
+    pub fn parse<S: AsRef<str>>(
+        day: u32,
+        lines: &[S],
+        expected: &str,
+        mut convert: impl FnMut(char) -> Option<T>,
+    ) -> Result<Self, ParseError> {
+        let width = lines
+            .first()
+            .map_or(0, |line| line.as_ref().chars().count());
+        let mut cells = Vec::with_capacity(width * lines.len());
+        for (i, line) in lines.iter().enumerate() {
+            let mut scanner = Scanner::new(day, i, line.as_ref());
+            for _ in 0..width {
+                cells.push(scanner.char_map(expected, &mut convert)?);
+            }
+            scanner.end()?;
+        }
+        Ok(Self {
+            width,
+            height: lines.len(),
+            cells,
+        })
+    }
+
+    /// Like [`Grid::parse`], but rows may be shorter than the widest one and are padded
+    /// with `fill`.
+    pub fn parse_ragged<S: AsRef<str>>(
+        day: u32,
+        lines: &[S],
+        expected: &str,
+        fill: T,
+        mut convert: impl FnMut(char) -> Option<T>,
+    ) -> Result<Self, ParseError>
+    where
+        T: Clone,
+    {
+        let width = lines
+            .iter()
+            .map(|line| line.as_ref().chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut cells = Vec::with_capacity(width * lines.len());
+        for (i, line) in lines.iter().enumerate() {
+            let mut scanner = Scanner::new(day, i, line.as_ref());
+            let mut len = 0;
+            while !scanner.is_empty() {
+                cells.push(scanner.char_map(expected, &mut convert)?);
+                len += 1;
+            }
+            cells.extend(std::iter::repeat_n(fill.clone(), width - len));
+        }
+        Ok(Self {
+            width,
+            height: lines.len(),
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, pos: Pos) -> bool {
+        pos.col >= 0
+            && pos.row >= 0
+            && (pos.col as usize) < self.width
+            && (pos.row as usize) < self.height
+    }
+
+    fn offset(&self, pos: Pos) -> Option<usize> {
+        self.contains(pos)
+            .then(|| pos.row as usize * self.width + pos.col as usize)
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.offset(pos).map(|offset| &self.cells[offset])
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        self.offset(pos).map(|offset| &mut self.cells[offset])
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    pub fn col(&self, col: usize) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator + '_ {
+        assert!(col < self.width, "column {} out of bounds", col);
+        self.cells.iter().skip(col).step_by(self.width)
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Pos> + '_ {
+        (0..self.height).flat_map(move |row| (0..self.width).map(move |col| pos(col, row)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> + '_ {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn find(&self, mut predicate: impl FnMut(&T) -> bool) -> Option<Pos> {
+        self.iter()
+            .find(|(_, value)| predicate(value))
+            .map(|(pos, _)| pos)
+    }
+
+    /// The up to 4 orthogonal neighbours of `pos` inside the grid.
+    pub fn neighbours4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        pos.neighbours4()
+            .into_iter()
+            .filter(|pos| self.contains(*pos))
+    }
+
+    /// The up to 8 neighbours of `pos`, diagonals included, inside the grid.
+    pub fn neighbours8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        pos.neighbours8()
+            .into_iter()
+            .filter(|pos| self.contains(*pos))
+    }
+
+    pub fn map<U>(&self, convert: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(convert).collect(),
+        }
+    }
+
+    /// One line per row, no trailing newline.
+    pub fn render(&self, mut cell: impl FnMut(&T) -> char) -> String {
+        let rows: Vec<String> = (0..self.height)
+            .map(|row| self.row(row).iter().map(&mut cell).collect())
+            .collect();
+        rows.join("\n")
+    }
+}
+
+fn pos(col: usize, row: usize) -> Pos {
+    Pos::new(col as i32, row as i32)
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &T {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{:?} is outside of the grid", pos))
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{:?} is outside of the grid", pos))
+    }
+}
+
+impl fmt::Display for Grid<char> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.render(|c| *c))
+    }
+}
+
+/// Unbounded grid that only stores occupied cells.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Pos, T>,
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self {
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, pos: Pos) -> bool {
+        self.cells.contains_key(&pos)
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.cells.get(&pos)
+    }
+
+    pub fn insert(&mut self, pos: Pos, value: T) -> Option<T> {
+        self.cells.insert(pos, value)
+    }
+
+    pub fn remove(&mut self, pos: Pos) -> Option<T> {
+        self.cells.remove(&pos)
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Pos> + '_ {
+        self.cells.keys().copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> + '_ {
+        self.cells.iter().map(|(pos, value)| (*pos, value))
+    }
+
+    /// Occupied neighbours of `pos`, diagonals included.
+    pub fn neighbours8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        pos.neighbours8()
+            .into_iter()
+            .filter(|pos| self.contains(*pos))
+    }
+
+    /// Top left and bottom right corners of the occupied area.
+    pub fn bounds(&self) -> Option<(Pos, Pos)> {
+        let mut positions = self.positions();
+        let first = positions.next()?;
+        Some(positions.fold((first, first), |(min, max), pos| {
+            (
+                Pos::new(min.col.min(pos.col), min.row.min(pos.row)),
+                Pos::new(max.col.max(pos.col), max.row.max(pos.row)),
+            )
+        }))
+    }
+
+    /// The occupied area as text, `empty` where nothing is stored.
+    pub fn render(&self, empty: char, mut cell: impl FnMut(&T) -> char) -> String {
+        let Some((min, max)) = self.bounds() else {
+            return String::new();
+        };
+        let rows: Vec<String> = (min.row..=max.row)
+            .map(|row| {
+                (min.col..=max.col)
+                    .map(|col| self.get(Pos::new(col, row)).map_or(empty, &mut cell))
+                    .collect()
+            })
+            .collect();
+        rows.join("\n")
+    }
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> FromIterator<(Pos, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Pos, T)>>(iter: I) -> Self {
+        Self {
+            cells: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits(lines: &[&str]) -> Result<Grid<u32>, ParseError> {
+        Grid::parse(8, lines, "a digit", |c| c.to_digit(10))
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = digits(&["123", "456"]).unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[Pos::new(2, 1)], 6);
+        assert_eq!(grid.get(Pos::new(3, 1)), None);
+        assert_eq!(grid.get(Pos::new(0, -1)), None);
+        assert_eq!(grid.row(1), &[4, 5, 6]);
+        assert_eq!(grid.col(1).copied().collect::<Vec<_>>(), vec![2, 5]);
+        assert_eq!(grid.find(|value| *value == 5), Some(Pos::new(1, 1)));
+        assert_eq!(
+            grid.render(|d| char::from_digit(*d, 10).unwrap()),
+            "123\n456"
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let error = digits(&["123", "4x6"]).unwrap_err();
+        assert_eq!(error, ParseError::new(8, 2, 2, "a digit", "x6"));
+        let error = digits(&["123", "45"]).unwrap_err();
+        assert_eq!(error, ParseError::new(8, 2, 3, "a digit", "end of line"));
+        let error = digits(&["123", "4567"]).unwrap_err();
+        assert_eq!(error, ParseError::new(8, 2, 4, "end of line", "7"));
+    }
+
+    #[test]
+    fn test_parse_ragged() {
+        let grid = Grid::parse_ragged(22, &["  .#", ".", ""], "a tile", ' ', Some).unwrap();
+        assert_eq!((grid.width(), grid.height()), (4, 3));
+        assert_eq!(grid.to_string(), "  .#\n.   \n    ");
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = Grid::new(3, 3, 0);
+        let corner: Vec<Pos> = grid.neighbours4(Pos::new(0, 0)).collect();
+        assert_eq!(corner, vec![Pos::new(1, 0), Pos::new(0, 1)]);
+        assert_eq!(grid.neighbours4(Pos::new(1, 1)).count(), 4);
+        assert_eq!(grid.neighbours8(Pos::new(1, 1)).count(), 8);
+        assert_eq!(grid.neighbours8(Pos::new(2, 0)).count(), 3);
+        assert_eq!(Pos::new(1, 1).manhattan(Pos::new(-2, 3)), 5);
+    }
+
+    #[test]
+    fn test_sparse() {
+        let mut grid: SparseGrid<char> = [(Pos::new(-1, 0), '#'), (Pos::new(1, 2), 'o')]
+            .into_iter()
+            .collect();
+        assert_eq!(grid.bounds(), Some((Pos::new(-1, 0), Pos::new(1, 2))));
+        assert_eq!(grid.render('.', |c| *c), "#..\n...\n..o");
+        assert_eq!(grid.neighbours8(Pos::new(0, 1)).count(), 2);
+
+        grid.remove(Pos::new(-1, 0));
+        assert_eq!(grid.len(), 1);
+        assert_eq!(grid.render('.', |c| *c), "o");
+        assert_eq!(SparseGrid::<char>::new().bounds(), None);
+    }
+}
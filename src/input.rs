@@ -0,0 +1,138 @@
+use std::ffi::OsString;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+use anyhow::{Context, Result};
+
+/// Directory to load puzzle inputs from instead of `resources/test/`.
+pub const INPUT_DIR_VAR: &str = "AOC_INPUT_DIR";
+
+/// Puzzle input with line endings normalised: CRLF becomes LF and trailing newlines are dropped,
+/// so files saved on any platform, with or without a final newline, read the same.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Input {
+    raw: String,
+}
+
+impl Input {
+    pub fn new(raw: &str) -> Self {
+        let raw = raw.replace("\r\n", "\n");
+        let len = raw.trim_end_matches('\n').len();
+        Self {
+            raw: raw[..len].to_owned(),
+        }
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        Ok(Self::new(&raw))
+    }
+
+    pub fn from_stdin() -> Result<Self> {
+        let mut raw = String::new();
+        io::stdin()
+            .read_to_string(&mut raw)
+            .context("failed to read stdin")?;
+        Ok(Self::new(&raw))
+    }
+
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+
+    pub fn lines(&self) -> Vec<&str> {
+        self.raw.lines().collect()
+    }
+
+    pub fn owned_lines(&self) -> Vec<String> {
+        self.raw.lines().map(|line| line.to_owned()).collect()
+    }
+}
+
+/// Finds inputs by name in one directory.
+pub struct Loader {
+    dir: PathBuf,
+}
+
+impl Loader {
+    /// `$AOC_INPUT_DIR` if set, `resources/test/` otherwise.
+    pub fn new() -> Self {
+        Self::with_dir(resolve_dir(env::var_os(INPUT_DIR_VAR)))
+    }
+
+    pub fn with_dir(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn load(&self, name: &str) -> Result<Input> {
+        Input::from_file(&self.dir.join(name))
+    }
+}
+
+impl Default for Loader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Loads `-` from stdin and anything else as a file path.
+pub fn load_path_or_stdin(path: &str) -> Result<Input> {
+    if path == "-" {
+        Input::from_stdin()
+    } else {
+        Input::from_file(Path::new(path))
+    }
+}
+
+fn resolve_dir(var: Option<OsString>) -> PathBuf {
+    match var {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => {
+            let mut dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            dir.push("resources/test/");
+            dir
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_endings() {
+        let expected = Input::new("1000\n\n2000");
+        assert_eq!(Input::new("1000\r\n\r\n2000\r\n"), expected);
+        assert_eq!(Input::new("1000\n\n2000\n\n"), expected);
+        assert_eq!(expected.lines(), vec!["1000", "", "2000"]);
+        assert_eq!(expected.raw(), "1000\n\n2000");
+
+        // leading and trailing spaces are part of some puzzles
+        assert_eq!(Input::new("  ..#  \n").raw(), "  ..#  ");
+    }
+
+    #[test]
+    fn test_load() {
+        let loader = Loader::with_dir(resolve_dir(None));
+        let input = loader.load("day1").unwrap();
+        assert_eq!(input.lines().len(), input.owned_lines().len());
+        assert!(!input.raw().ends_with('\n'));
+
+        let error = loader.load("day16").unwrap_err();
+        assert!(format!("{:#}", error).starts_with("failed to read "));
+    }
+
+    #[test]
+    fn test_resolve_dir() {
+        let dir = resolve_dir(Some(OsString::from("/tmp/inputs")));
+        assert_eq!(dir, PathBuf::from("/tmp/inputs"));
+        assert!(resolve_dir(None).ends_with("resources/test"));
+        assert_eq!(resolve_dir(Some(OsString::new())), resolve_dir(None));
+    }
+}
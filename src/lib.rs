@@ -13,6 +13,8 @@ pub mod day12;
 pub mod day13;
 pub mod day14;
 pub mod day15;
+pub mod day16;
+pub mod day17;
 pub mod day18;
 pub mod day19;
 pub mod day20;
@@ -23,4 +25,13 @@ pub mod day24;
 pub mod day25;
 pub mod day1_2023;
 
+pub mod answers;
+pub mod bench;
+pub mod error;
+pub mod grid;
+pub mod input;
+pub mod ocr;
+pub mod registry;
+pub mod search;
+pub mod solution;
 pub mod util;
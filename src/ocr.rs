@@ -0,0 +1,98 @@
+use anyhow::{bail, Result};
+
+use crate::grid::Grid;
+
+const LETTER_WIDTH: usize = 4;
+const LETTER_HEIGHT: usize = 6;
+
+/// The block letters AoC draws on 6 pixel high screens, each 4 pixels wide with a blank
+/// column between letters.
+const FONT: [(char, [&str; LETTER_HEIGHT]); 17] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+/// Reads the letters drawn with `#` on `screen`.
+pub fn read(screen: &Grid<char>) -> Result<String> {
+    if screen.height() != LETTER_HEIGHT {
+        bail!(
+            "expected a screen {} pixels high, found {}",
+            LETTER_HEIGHT,
+            screen.height()
+        );
+    }
+    let mut result = String::new();
+    for left in (0..screen.width()).step_by(LETTER_WIDTH + 1) {
+        let glyph: Vec<&[char]> = (0..LETTER_HEIGHT)
+            .map(|row| {
+                let row = screen.row(row);
+                &row[left..(left + LETTER_WIDTH).min(row.len())]
+            })
+            .collect();
+        let letter = FONT.iter().find(|(_, pattern)| {
+            pattern
+                .iter()
+                .zip(glyph.iter())
+                .all(|(expected, found)| expected.chars().eq(found.iter().copied()))
+        });
+        match letter {
+            Some((letter, _)) => result.push(*letter),
+            None => bail!("unknown letter at column {}", left + 1),
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen(lines: &[&str]) -> Grid<char> {
+        Grid::parse(0, lines, "'.' or '#'", |c| {
+            matches!(c, '.' | '#').then_some(c)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_read() {
+        let screen = screen(&[
+            "#..#.####.#....###..",
+            "#..#.#....#....#..#.",
+            "####.###..#....#..#.",
+            "#..#.#....#....###..",
+            "#..#.#....#....#.#..",
+            "#..#.####.####.#..#.",
+        ]);
+        assert_eq!(read(&screen).unwrap(), "HELR");
+    }
+
+    #[test]
+    fn test_read_error() {
+        let screen = screen(&["#..#.", "#..#.", "####.", "#..#.", "#..#.", "#.##."]);
+        assert_eq!(
+            read(&screen).unwrap_err().to_string(),
+            "unknown letter at column 1"
+        );
+        let error = read(&Grid::new(5, 5, '.')).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "expected a screen 6 pixels high, found 5"
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::BTreeMap;
+
+use crate::solution::{Erased, Solution, Solver};
+use crate::*;
+
+pub struct Registry {
+    solvers: BTreeMap<(u32, u32), Box<dyn Solver>>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        let mut registry = Registry {
+            solvers: BTreeMap::new(),
+        };
+        registry.register::<day1::Day1>();
+        registry.register::<day2::Day2>();
+        registry.register::<day3::Day3>();
+        registry.register::<day4::Day4>();
+        registry.register::<day5::Day5>();
+        registry.register::<day6::Day6>();
+        registry.register::<day7::Day7>();
+        registry.register::<day8::Day8>();
+        registry.register::<day9::Day9>();
+        registry.register::<day10::Day10>();
+        registry.register::<day11::Day11>();
+        registry.register::<day12::Day12>();
+        registry.register::<day13::Day13>();
+        registry.register::<day14::Day14>();
+        registry.register::<day15::Day15>();
+        registry.register::<day16::Day16>();
+        registry.register::<day17::Day17>();
+        registry.register::<day18::Day18>();
+        registry.register::<day19::Day19>();
+        registry.register::<day20::Day20>();
+        registry.register::<day21::Day21>();
+        registry.register::<day22::Day22>();
+        registry.register::<day23::Day23>();
+        registry.register::<day24::Day24>();
+        registry.register::<day25::Day25>();
+        registry.register::<day1_2023::Day1>();
+        registry
+    }
+
+    pub fn register<S: Solution + 'static>(&mut self) {
+        let key = (S::YEAR, S::DAY);
+        let previous = self.solvers.insert(key, Box::new(Erased::<S>::new()));
+        assert!(previous.is_none(), "day {:?} is registered twice", key);
+    }
+
+    pub fn get(&self, year: u32, day: u32) -> Option<&dyn Solver> {
+        self.solvers.get(&(year, day)).map(|solver| solver.as_ref())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Solver> {
+        self.solvers.values().map(|solver| solver.as_ref())
+    }
+}
+
+// resources/test naming: 2022 days are plain `dayN`, other years get a suffix.
+// 2022 day 10 runs on `day10_big`: `day10` is the example, whose screen shows no letters.
+pub fn input_name(year: u32, day: u32) -> String {
+    match (year, day) {
+        (2022, 10) => "day10_big".to_owned(),
+        (2022, _) => format!("day{}", day),
+        _ => format!("day{}_{}", day, year),
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::Part;
+
+    #[test]
+    fn test_get() {
+        let registry = Registry::new();
+        let solver = registry.get(2022, 1).unwrap();
+        let input = solver.parse("1000\n2000\n\n4000\n").unwrap();
+        assert_eq!(input.solve(Part::One).unwrap(), "4000");
+        assert_eq!(input.solve(Part::Two).unwrap(), "7000");
+        assert!(registry.get(2021, 1).is_none());
+
+        let error = solver.parse("1000\nabc\n").err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "day 1: line 2, column 1: expected a number, found \"abc\""
+        );
+    }
+
+    #[test]
+    fn test_iter_is_ordered() {
+        let registry = Registry::new();
+        let keys: Vec<(u32, u32)> = registry
+            .iter()
+            .map(|solver| (solver.year(), solver.day()))
+            .collect();
+        assert_eq!(keys.first(), Some(&(2022, 1)));
+        assert_eq!(keys.last(), Some(&(2023, 1)));
+        let mut sorted = keys.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(keys, sorted);
+        assert!(keys.contains(&(2022, 25)));
+    }
+
+    #[test]
+    fn test_input_name() {
+        assert_eq!(input_name(2022, 7), "day7");
+        assert_eq!(input_name(2022, 10), "day10_big");
+        assert_eq!(input_name(2023, 1), "day1_2023");
+    }
+}
@@ -0,0 +1,241 @@
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+/// A cheapest route found by a search, `states` runs from a start to the goal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<S> {
+    pub cost: u32,
+    pub states: Vec<S>,
+}
+
+impl<S> Path<S> {
+    pub fn goal(&self) -> &S {
+        self.states.last().unwrap()
+    }
+}
+
+struct Node<S> {
+    state: S,
+    parent: Option<usize>,
+    cost: u32,
+}
+
+/// Every state seen so far, with the index of its cheapest node.
+struct Tree<S> {
+    nodes: Vec<Node<S>>,
+    best: HashMap<S, usize>,
+}
+
+impl<S: Clone + Eq + Hash> Tree<S> {
+    fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            best: HashMap::new(),
+        }
+    }
+
+    /// Records `state` if it's new or cheaper than before and returns its node.
+    fn reach(&mut self, state: S, parent: Option<usize>, cost: u32) -> Option<usize> {
+        let index = self.nodes.len();
+        match self.best.entry(state.clone()) {
+            Entry::Occupied(entry) if self.nodes[*entry.get()].cost <= cost => return None,
+            Entry::Occupied(mut entry) => {
+                entry.insert(index);
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(index);
+            }
+        }
+        self.nodes.push(Node {
+            state,
+            parent,
+            cost,
+        });
+        Some(index)
+    }
+
+    fn is_stale(&self, index: usize) -> bool {
+        self.best[&self.nodes[index].state] != index
+    }
+
+    fn path(&self, mut index: usize) -> Path<S> {
+        let cost = self.nodes[index].cost;
+        let mut states = vec![self.nodes[index].state.clone()];
+        while let Some(parent) = self.nodes[index].parent {
+            states.push(self.nodes[parent].state.clone());
+            index = parent;
+        }
+        states.reverse();
+        Path { cost, states }
+    }
+}
+
+/// Breadth-first search where every step costs 1. Searches from all `starts` at once.
+pub fn bfs<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut successors: impl FnMut(&S) -> I,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S>>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut tree = Tree::new();
+    let mut queue: VecDeque<usize> = starts
+        .into_iter()
+        .filter_map(|start| tree.reach(start, None, 0))
+        .collect();
+    while let Some(index) = queue.pop_front() {
+        let Node { state, cost, .. } = &tree.nodes[index];
+        if is_goal(state) {
+            return Some(tree.path(index));
+        }
+        let cost = cost + 1;
+        for next in successors(state) {
+            if let Some(next) = tree.reach(next, Some(index), cost) {
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Every state reachable from `starts`, the starts included.
+pub fn reachable<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut successors: impl FnMut(&S) -> I,
+) -> HashSet<S>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut seen = HashSet::new();
+    let mut queue: VecDeque<S> = starts
+        .into_iter()
+        .filter(|start| seen.insert(start.clone()))
+        .collect();
+    while let Some(state) = queue.pop_front() {
+        for next in successors(&state) {
+            if seen.insert(next.clone()) {
+                queue.push_back(next);
+            }
+        }
+    }
+    seen
+}
+
+/// Cheapest path when successors come with the cost of the step to them.
+pub fn dijkstra<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    successors: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S>>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, u32)>,
+{
+    astar(starts, successors, |_| 0, is_goal)
+}
+
+/// Like [`dijkstra`], guided by `heuristic`, which must never overestimate the remaining
+/// cost to a goal.
+pub fn astar<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut successors: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> u32,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S>>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, u32)>,
+{
+    let mut tree = Tree::new();
+    let mut queue = BinaryHeap::new();
+    for start in starts {
+        let estimate = heuristic(&start);
+        if let Some(index) = tree.reach(start, None, 0) {
+            queue.push(Reverse((estimate, index)));
+        }
+    }
+    while let Some(Reverse((_, index))) = queue.pop() {
+        if tree.is_stale(index) {
+            continue;
+        }
+        let Node { state, cost, .. } = &tree.nodes[index];
+        if is_goal(state) {
+            return Some(tree.path(index));
+        }
+        let cost = *cost;
+        for (next, step) in successors(state) {
+            let estimate = cost + step + heuristic(&next);
+            if let Some(next) = tree.reach(next, Some(index), cost + step) {
+                queue.push(Reverse((estimate, next)));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{Grid, Pos};
+
+    fn maze() -> Grid<bool> {
+        let lines = ["..#....", ".##.##.", "...#...", ".#...#."];
+        Grid::parse(0, &lines, "'.' or '#'", |c| match c {
+            '.' => Some(true),
+            '#' => Some(false),
+            _ => None,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_bfs() {
+        let maze = maze();
+        let successors =
+            |pos: &Pos| -> Vec<Pos> { maze.neighbours4(*pos).filter(|next| maze[*next]).collect() };
+        let goal = Pos::new(6, 3);
+        let path = bfs([Pos::new(0, 0)], successors, |pos| *pos == goal).unwrap();
+        assert_eq!(path.cost, 11);
+        assert_eq!(path.states.len(), 12);
+        assert_eq!(path.states[0], Pos::new(0, 0));
+        assert_eq!(*path.goal(), goal);
+        for step in path.states.windows(2) {
+            assert_eq!(step[0].manhattan(step[1]), 1);
+        }
+
+        let path = bfs([Pos::new(0, 0), Pos::new(6, 0)], successors, |pos| {
+            *pos == goal
+        });
+        assert_eq!(path.unwrap().cost, 3);
+        assert_eq!(bfs([Pos::new(0, 0)], successors, |_| false), None);
+        assert_eq!(reachable([Pos::new(0, 0)], successors).len(), 20);
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        // going through 1 costs more than going around it
+        let edges = HashMap::from([
+            (0, vec![(1, 10), (2, 1)]),
+            (1, vec![(3, 1)]),
+            (2, vec![(4, 2)]),
+            (4, vec![(1, 1), (3, 7)]),
+        ]);
+        let successors = |node: &u32| edges.get(node).cloned().unwrap_or_default();
+        let path = dijkstra([0], successors, |node| *node == 3).unwrap();
+        assert_eq!(path.cost, 5);
+        assert_eq!(path.states, vec![0, 2, 4, 1, 3]);
+
+        let path = astar(
+            [0],
+            successors,
+            |node| u32::from(*node != 3),
+            |node| *node == 3,
+        );
+        assert_eq!(path.unwrap().cost, 5);
+    }
+}
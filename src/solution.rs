@@ -0,0 +1,140 @@
+use std::fmt::{self, Display};
+use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe};
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+
+/// Common shape of a puzzle solver: parse the input once, then answer both parts.
+pub trait Solution {
+    const YEAR: u32;
+    const DAY: u32;
+
+    type Input;
+    type Part1: Display;
+    type Part2: Display;
+
+    fn parse(input: &str) -> Result<Self::Input>;
+    fn part1(input: &Self::Input) -> Result<Self::Part1>;
+    fn part2(input: &Self::Input) -> Result<Self::Part2>;
+}
+
+/// Answer for a part that has no solver (yet).
+#[derive(Debug, PartialEq, Eq)]
+pub struct Unsolved;
+
+impl Display for Unsolved {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unsolved")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl FromStr for Part {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "1" => Ok(Part::One),
+            "2" => Ok(Part::Two),
+            _ => Err(format!("unknown part {}, expected 1 or 2", s)),
+        }
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Part::One => write!(f, "1"),
+            Part::Two => write!(f, "2"),
+        }
+    }
+}
+
+/// Object-safe view of a [`Solution`], so solvers of different types can live in one registry.
+pub trait Solver: Sync {
+    fn year(&self) -> u32;
+    fn day(&self) -> u32;
+    fn parse(&self, input: &str) -> Result<Box<dyn Parsed>>;
+}
+
+/// Parsed input of a [`Solver`], ready to answer either part.
+pub trait Parsed {
+    fn part1(&self) -> Result<String>;
+    fn part2(&self) -> Result<String>;
+
+    fn solve(&self, part: Part) -> Result<String> {
+        match part {
+            Part::One => self.part1(),
+            Part::Two => self.part2(),
+        }
+    }
+}
+
+pub struct Erased<S>(PhantomData<fn() -> S>);
+
+impl<S> Erased<S> {
+    pub const fn new() -> Self {
+        Erased(PhantomData)
+    }
+}
+
+impl<S> Default for Erased<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Solver for Erased<S>
+where
+    S: Solution + 'static,
+{
+    fn year(&self) -> u32 {
+        S::YEAR
+    }
+
+    fn day(&self) -> u32 {
+        S::DAY
+    }
+
+    fn parse(&self, input: &str) -> Result<Box<dyn Parsed>> {
+        Ok(Box::new(ParsedInput::<S>(S::parse(input)?)))
+    }
+}
+
+struct ParsedInput<S: Solution>(S::Input);
+
+impl<S: Solution> Parsed for ParsedInput<S> {
+    fn part1(&self) -> Result<String> {
+        Ok(S::part1(&self.0)?.to_string())
+    }
+
+    fn part2(&self) -> Result<String> {
+        Ok(S::part2(&self.0)?.to_string())
+    }
+}
+
+/// Parses `input` once and answers `parts` in order. A panicking solver becomes an error,
+/// so one broken day can't take a whole batch run down with it.
+pub fn solve(solver: &dyn Solver, input: &str, parts: &[Part]) -> Result<Vec<String>> {
+    solve_each(solver, input, parts)?.into_iter().collect()
+}
+
+/// Like [`solve`], but each part succeeds or fails on its own. Only a parse error fails
+/// them all.
+pub fn solve_each(solver: &dyn Solver, input: &str, parts: &[Part]) -> Result<Vec<Result<String>>> {
+    let parsed = catch_panic(|| solver.parse(input))?;
+    Ok(parts
+        .iter()
+        .map(|part| catch_panic(|| parsed.solve(*part)))
+        .collect())
+}
+
+fn catch_panic<T>(f: impl FnOnce() -> Result<T>) -> Result<T> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|_| anyhow!("solver panicked"))?
+}
@@ -1,12 +1,9 @@
-use std::fs;
-use std::path::PathBuf;
+use crate::input::Loader;
 
+// tests can't do anything useful without their input, so a missing file fails loudly
 pub fn read_real_data(file: &str) -> Vec<String> {
-    let mut dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    dir.push("resources/test/");
-    dir.push(file);
-
-    let contents =
-        fs::read_to_string(&dir).unwrap_or_else(|_| format!("failed to read from: {:?}", dir));
-    contents.lines().map(|line| line.to_owned()).collect()
+    Loader::new()
+        .load(file)
+        .unwrap_or_else(|err| panic!("{:#}", err))
+        .owned_lines()
 }